@@ -15,6 +15,11 @@ mod image;
 mod button;
 mod anchor;
 mod media_query_list;
+mod mouse_event;
+mod select;
+mod text_area;
+mod data_transfer;
+mod clipboard;
 
 pub use input::{
     input, event_current_target,
@@ -22,9 +27,14 @@ pub use input::{
 pub use file_reader::{
     file_reader, file_reader_result, file_reader_read_as_data_url,
 };
+#[cfg(feature = "native-decode")]
+pub use file_reader::{
+    file_reader_result_bytes, file_reader_read_as_array_buffer,
+};
 pub use canvas::{
     canvas, context_from_canvas, get_canvas_size, create_canvas,
-    canvas_to_data_url, get_canvas_display_size,
+    canvas_to_data_url, canvas_to_png_blob, get_canvas_display_size,
+    add_event_listener_with_callback_canvas, set_canvas_class,
 };
 pub use image::{
     get_context_image_data, image_data, new_image,
@@ -34,9 +44,22 @@ pub use button::{
     add_event_listener_with_callback_button,
 };
 pub use anchor::create_anchor;
+pub use select::{
+    select, add_event_listener_with_callback_select,
+};
+pub use text_area::text_area;
+pub use clipboard::clipboard_write_png;
+pub use data_transfer::{
+    drag_event_image_file, drag_event_set_drop_effect_copy, clipboard_event_image_file,
+};
+pub use mouse_event::{
+    event_to_mouse_event, mouse_event_offset,
+    pointer_event_id, wheel_event_delta_y,
+};
 pub use media_query_list::{
-    make_media_query_list, get_color_scheme_media_query_list,
-    add_listener_with_opt_callback, event_current_target_to_media_query_list,
+    make_media_query_list, get_color_scheme_media_query_list, get_pixel_ratio_media_query_list,
+    add_listener_with_opt_callback, remove_listener_with_opt_callback,
+    event_current_target_to_media_query_list,
 };
 
 pub type EventClosure = Closure<dyn FnMut(Event)>;
@@ -61,6 +84,17 @@ pub fn window() -> Result<Window> {
     web_sys::window().ok_or_else(|| anyhow!("No Window Found"))
 }
 
+/// Device pixels per CSS pixel.
+pub fn device_pixel_ratio() -> Result<f64> {
+    Ok(window()?.device_pixel_ratio())
+}
+
+pub fn add_event_listener_with_callback_window(event_name: &str, closure: &EventClosure) -> Result<()> {
+    window()?
+        .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Error adding event listener with callback {:#?}", err))
+}
+
 pub fn document() -> Result<Document> {
     window()?.document().ok_or_else(|| anyhow!("No Document Found"))
 }
@@ -90,6 +124,14 @@ pub fn label(id: &str) -> Result<HtmlLabelElement> {
         .map_err(|element| anyhow!("Error converting {:#?} to HtmlLabelElement", element))
 }
 
+pub fn html_element(id: &str) -> Result<HtmlElement> {
+    document()?
+        .get_element_by_id(id)
+        .ok_or_else(|| anyhow!("No Element found with ID {}", id))?
+        .dyn_into::<HtmlElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))
+}
+
 // pub fn css_style_set_property(
 //     style: &CssStyleDeclaration,
 //     property: &str,
@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
-use wasm_bindgen::JsCast;
+use js_sys::Promise;
+use wasm_bindgen::{JsCast, JsValue};
 use web_sys::{
     CanvasRenderingContext2d, HtmlCanvasElement,
 };
@@ -42,6 +43,33 @@ pub fn canvas_to_data_url(canvas: &HtmlCanvasElement) -> Result<String> {
         .map_err(|err| anyhow!("Error converting canvas to data url {:#?}", err))
 }
 
+/// Promise of the canvas encoded as a PNG blob.
+pub fn canvas_to_png_blob(canvas: &HtmlCanvasElement) -> Promise {
+    Promise::new(&mut |resolve, reject| {
+        if let Err(err) = canvas.to_blob(&resolve) {
+            let _ = reject.call1(&JsValue::NULL, &err);
+        }
+    })
+}
+
+pub fn add_event_listener_with_callback_canvas(
+    canvas: &HtmlCanvasElement,
+    event_name: &str,
+    closure: &super::EventClosure,
+) -> Result<()> {
+    canvas.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Error adding event listener with callback {:#?}", err))
+}
+
+pub fn set_canvas_class(canvas: &HtmlCanvasElement, class: &str, enabled: bool) -> Result<()> {
+    let class_list = canvas.class_list();
+    if enabled {
+        class_list.add_1(class)
+    } else {
+        class_list.remove_1(class)
+    }.map_err(|err| anyhow!("Error setting class {:#?}: {:#?}", class, err))
+}
+
 pub fn context_from_canvas(canvas: &HtmlCanvasElement) -> Result<CanvasRenderingContext2d> {
     canvas
         .get_context("2d")
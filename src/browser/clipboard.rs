@@ -0,0 +1,31 @@
+use anyhow::{anyhow, Result};
+use js_sys::{Array, Object, Promise, Reflect};
+use wasm_bindgen::{JsValue, prelude::wasm_bindgen};
+use wasm_bindgen_futures::JsFuture;
+
+// The Clipboard API is behind the unstable APIs of web-sys.
+#[wasm_bindgen]
+extern "C" {
+    type ClipboardItem;
+
+    #[wasm_bindgen(constructor, catch)]
+    fn new(items: &Object) -> Result<ClipboardItem, JsValue>;
+
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = write, catch)]
+    fn clipboard_write(items: &Array) -> Result<Promise, JsValue>;
+}
+
+/// Starts writing a PNG to the clipboard and returns the future finishing the write.
+///
+/// The write has to start while a click is handled, so `png` is passed as a promise
+/// that may still be encoding.
+pub fn clipboard_write_png(png: &Promise) -> Result<JsFuture> {
+    let items = Object::new();
+    Reflect::set(&items, &JsValue::from_str("image/png"), png)
+        .map_err(|err| anyhow!("Error setting clipboard item {:#?}", err))?;
+    let item = ClipboardItem::new(&items)
+        .map_err(|err| anyhow!("Error creating ClipboardItem {:#?}", err))?;
+    let promise = clipboard_write(&Array::of1(&item))
+        .map_err(|err| anyhow!("Error writing to the clipboard {:#?}", err))?;
+    Ok(JsFuture::from(promise))
+}
@@ -0,0 +1,46 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::{JsCast, prelude::wasm_bindgen};
+use web_sys::{
+    DataTransfer, DragEvent, Event, File,
+};
+
+// `ClipboardEvent` is behind the unstable APIs of web-sys, and only its data is needed.
+#[wasm_bindgen]
+extern "C" {
+    type ClipboardEvent;
+
+    #[wasm_bindgen(method, getter, js_name = clipboardData)]
+    fn clipboard_data(this: &ClipboardEvent) -> Option<DataTransfer>;
+}
+
+fn first_image_file(data_transfer: Option<DataTransfer>) -> Option<File> {
+    let files = data_transfer?.files()?;
+    (0..files.length())
+        .filter_map(|index| files.get(index))
+        .find(|file| file.type_().starts_with("image/"))
+}
+
+/// First image file dragged with the event.
+pub fn drag_event_image_file(event: &Event) -> Result<Option<File>> {
+    let drag_event = event.clone()
+        .dyn_into::<DragEvent>()
+        .map_err(|element| anyhow!("Error converting {:#?} to DragEvent", element))?;
+    Ok(first_image_file(drag_event.data_transfer()))
+}
+
+/// Shows the dragged data will be copied, rather than moved or linked.
+pub fn drag_event_set_drop_effect_copy(event: &Event) -> Result<()> {
+    let drag_event = event.clone()
+        .dyn_into::<DragEvent>()
+        .map_err(|element| anyhow!("Error converting {:#?} to DragEvent", element))?;
+    if let Some(data_transfer) = drag_event.data_transfer() {
+        data_transfer.set_drop_effect("copy");
+    }
+    Ok(())
+}
+
+/// First image file pasted with the event.
+pub fn clipboard_event_image_file(event: &Event) -> Result<Option<File>> {
+    let clipboard_event = event.unchecked_ref::<ClipboardEvent>();
+    Ok(first_image_file(clipboard_event.clipboard_data()))
+}
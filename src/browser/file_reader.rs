@@ -1,4 +1,6 @@
 use anyhow::{anyhow, Result};
+#[cfg(feature = "native-decode")]
+use js_sys::Uint8Array;
 use web_sys::{
     FileReader, File,
 };
@@ -15,6 +17,20 @@ pub fn file_reader_result(file_reader: &FileReader) -> Result<String> {
         .ok_or_else(|| anyhow!("Could not get result as string from FileReader"))
 }
 
+/// Bytes read by `file_reader_read_as_array_buffer`.
+#[cfg(feature = "native-decode")]
+pub fn file_reader_result_bytes(file_reader: &FileReader) -> Result<Vec<u8>> {
+    let result = file_reader.result()
+        .map_err(|err| anyhow!("Could not get result from FileReader {:#?}", err))?;
+    Ok(Uint8Array::new(&result).to_vec())
+}
+
+#[cfg(feature = "native-decode")]
+pub fn file_reader_read_as_array_buffer(file_reader: &FileReader, file: &File) -> Result<()> {
+    file_reader.read_as_array_buffer(file)
+        .map_err(|err| anyhow!("Could not read file {:#?}", err))
+}
+
 pub fn file_reader_read_as_data_url(file_reader: &FileReader, file: &File) -> Result<()> {
     file_reader.read_as_data_url(file)
         .map_err(|err| anyhow!("Could not read file {:#?}", err))
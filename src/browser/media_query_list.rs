@@ -18,6 +18,16 @@ pub fn get_color_scheme_media_query_list() -> Result<MediaQueryList> {
     }
 }
 
+/// Media query that stops matching when the device pixel ratio changes from `ratio`.
+pub fn get_pixel_ratio_media_query_list(ratio: f64) -> Result<MediaQueryList> {
+    let media_query_list = make_media_query_list(&format!("(resolution: {}dppx)", ratio))?;
+    if let Some(media_query_list) = media_query_list {
+        Ok(media_query_list)
+    } else {
+        Err(anyhow!("No media query list found"))
+    }
+}
+
 pub fn add_listener_with_opt_callback(
     media_query_list: &MediaQueryList,
     closure: Option<&super::EventClosure>,
@@ -28,6 +38,16 @@ pub fn add_listener_with_opt_callback(
         )).map_err(|err| anyhow!("Error adding listener with opt callback {:#?}", err))
 }
 
+pub fn remove_listener_with_opt_callback(
+    media_query_list: &MediaQueryList,
+    closure: Option<&super::EventClosure>,
+) -> Result<()> {
+    media_query_list
+        .remove_listener_with_opt_callback(closure.map(
+            |closure| closure.as_ref().unchecked_ref()
+        )).map_err(|err| anyhow!("Error removing listener with opt callback {:#?}", err))
+}
+
 pub fn event_current_target_to_media_query_list(event: &Event) -> Result<MediaQueryList> {
     event.current_target()
         .ok_or_else(|| anyhow!("No current target found"))?
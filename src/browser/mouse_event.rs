@@ -0,0 +1,34 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+use web_sys::{Event, MouseEvent, PointerEvent, WheelEvent};
+
+pub fn event_to_mouse_event(event: &Event) -> Result<MouseEvent> {
+    event.clone()
+        .dyn_into::<MouseEvent>()
+        .map_err(|element| anyhow!("Error converting {:#?} to MouseEvent", element))
+}
+
+pub fn mouse_event_offset(event: &Event) -> Result<(f64, f64)> {
+    let mouse_event = event_to_mouse_event(event)?;
+    Ok((mouse_event.offset_x() as f64, mouse_event.offset_y() as f64))
+}
+
+pub fn pointer_event_id(event: &Event) -> Result<i32> {
+    let pointer_event = event.clone()
+        .dyn_into::<PointerEvent>()
+        .map_err(|element| anyhow!("Error converting {:#?} to PointerEvent", element))?;
+    Ok(pointer_event.pointer_id())
+}
+
+/// Vertical scroll amount of a wheel event in pixels.
+pub fn wheel_event_delta_y(event: &Event) -> Result<f64> {
+    let wheel_event = event.clone()
+        .dyn_into::<WheelEvent>()
+        .map_err(|element| anyhow!("Error converting {:#?} to WheelEvent", element))?;
+    let scale = match wheel_event.delta_mode() {
+        WheelEvent::DOM_DELTA_LINE => 16.0,
+        WheelEvent::DOM_DELTA_PAGE => 800.0,
+        _ => 1.0,
+    };
+    Ok(wheel_event.delta_y() * scale)
+}
@@ -0,0 +1,22 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlSelectElement;
+
+use super::document;
+
+pub fn select(id: &str) -> Result<HtmlSelectElement> {
+    document()?
+        .get_element_by_id(id)
+        .ok_or_else(|| anyhow!("No Select Element found with ID {}", id))?
+        .dyn_into::<HtmlSelectElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlSelectElement", element))
+}
+
+pub fn add_event_listener_with_callback_select(
+    element: &HtmlSelectElement,
+    event_name: &str,
+    closure: &super::EventClosure,
+) -> Result<()> {
+    element.add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        .map_err(|err| anyhow!("Error adding event listener with callback {:#?}", err))
+}
@@ -0,0 +1,13 @@
+use anyhow::{anyhow, Result};
+use wasm_bindgen::JsCast;
+use web_sys::HtmlTextAreaElement;
+
+use super::document;
+
+pub fn text_area(id: &str) -> Result<HtmlTextAreaElement> {
+    document()?
+        .get_element_by_id(id)
+        .ok_or_else(|| anyhow!("No TextArea Element found with ID {}", id))?
+        .dyn_into::<HtmlTextAreaElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlTextAreaElement", element))
+}
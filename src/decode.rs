@@ -0,0 +1,71 @@
+use anyhow::{Result, anyhow};
+use image::ImageFormat;
+
+/// Formats decoded in Rust, the others are left to the browser.
+const FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Bmp,
+    ImageFormat::Gif,
+];
+
+/// Decodes a PNG, JPEG, BMP or the first frame of a GIF to straight RGBA.
+///
+/// Unlike drawing an image element, no color management or premultiplication is applied,
+/// so the values are the same in every browser.
+pub fn decode_image(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32)> {
+    let format = image::guess_format(bytes)
+        .map_err(|err| anyhow!("Could not detect the image format {:#?}", err))?;
+    if !FORMATS.contains(&format) {
+        return Err(anyhow!("Decoding {:?} is left to the browser", format));
+    }
+    let image = image::load_from_memory_with_format(bytes, format)
+        .map_err(|err| anyhow!("Could not decode the image {:#?}", err))?
+        .into_rgba8();
+    let (width, height) = image.dimensions();
+    Ok((image.into_raw(), width, height))
+}
+
+#[cfg(all(test, feature = "native-decode"))]
+mod tests {
+    use std::io::Cursor;
+    use image::{DynamicImage, ImageOutputFormat, RgbImage, RgbaImage};
+    use super::*;
+
+    fn encode(image: DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn png_keeps_the_exact_straight_rgba_values() {
+        let pixels = vec![
+            255, 0, 0, 255, 0, 255, 0, 128, 0, 0, 255, 0,
+            200, 100, 50, 1, 1, 2, 3, 254, 255, 255, 255, 255,
+        ];
+        let png = encode(DynamicImage::ImageRgba8(RgbaImage::from_raw(3, 2, pixels.clone()).unwrap()),
+            ImageOutputFormat::Png);
+        assert_eq!(decode_image(&png).unwrap(), (pixels, 3, 2));
+    }
+
+    #[test]
+    fn bmp_is_decoded_opaque() {
+        let rgb = vec![10, 20, 30, 40, 50, 60];
+        let bmp = encode(DynamicImage::ImageRgb8(RgbImage::from_raw(1, 2, rgb).unwrap()),
+            ImageOutputFormat::Bmp);
+        assert_eq!(decode_image(&bmp).unwrap(), (vec![10, 20, 30, 255, 40, 50, 60, 255], 1, 2));
+    }
+
+    #[test]
+    fn webp_is_left_to_the_browser() {
+        let webp = b"RIFF\x1a\x00\x00\x00WEBPVP8L\x0d\x00\x00\x00\x2f\x00\x00\x00\x10\x07\x10\x11\x11\x88\x88\xfe\x07\x00";
+        let err = decode_image(webp).unwrap_err();
+        assert!(err.to_string().contains("WebP"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_bytes_are_an_error() {
+        assert!(decode_image(b"not an image").is_err());
+    }
+}
@@ -2,9 +2,10 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::{Mutex, MutexGuard};
 use anyhow::Result;
+use crate::browser;
 use crate::engine::{
     self, Image, Renderer, ImageDataWrapper, Canvas, DisplayElement,
-    Input, Button, Rect, Label,
+    Input, Button, Rect, Label, Select, TextArea,
 };
 
 mod input;
@@ -14,9 +15,32 @@ mod image_data_list;
 mod back_and_forward;
 mod mode_manager;
 mod theme;
+mod filter;
+mod contrast;
+mod tool;
+mod white_balance;
+mod effect;
+mod blur;
+mod preview;
+mod sharpen;
+mod convolution;
+mod edge_detection;
+mod denoise;
+mod morphology;
+mod crop;
+mod orientation;
+mod rotate;
+mod resize;
+mod canvas_size;
+mod trim;
+mod perspective;
+mod viewport;
+mod compare;
+mod inspector;
 
 pub use image_data_list::ImageDataList;
 pub use mode_manager::{Mode, ModeManager};
+pub use tool::Tool;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum EditorElement {
@@ -24,27 +48,159 @@ pub enum EditorElement {
     Input,
     Binarization,
     Save,
+    Copy,
+    #[cfg(feature = "native-decode")]
+    NativeDecode,
     Undo,
     Redo,
+    HistogramEqualization,
+    Clahe,
+    ClaheTileGrid,
+    ClaheClipLimit,
+    GrayWorld,
+    WhitePatch,
+    PercentileStretch,
+    StretchPercent,
+    NeutralPick,
+    Invert,
+    InvertPreserveAlpha,
+    Sepia,
+    SepiaStrength,
+    Posterize,
+    PosterizeLevels,
+    Solarize,
+    SolarizeThreshold,
+    Duotone,
+    DuotoneDark,
+    DuotoneLight,
+    Blur,
+    BlurKind,
+    BlurRadius,
+    BlurEdgeMode,
+    Sharpen,
+    SharpenPreview,
+    UnsharpMask,
+    UnsharpMaskPreview,
+    UnsharpAmount,
+    UnsharpRadius,
+    UnsharpThreshold,
+    KernelPreset,
+    KernelText,
+    KernelDivisor,
+    KernelBias,
+    KernelChannels,
+    KernelEdgeMode,
+    Convolution,
+    ConvolutionPreview,
+    GradientOperator,
+    GradientDirection,
+    Gradient,
+    LogRadius,
+    LogThreshold,
+    LaplacianOfGaussian,
+    CannyRadius,
+    CannyLow,
+    CannyHigh,
+    Canny,
+    MedianRadius,
+    Median,
+    BilateralRadius,
+    BilateralSigmaColor,
+    Bilateral,
+    NlmSearchRadius,
+    NlmStrength,
+    NonLocalMeans,
+    MorphologyOperation,
+    MorphologyElement,
+    MorphologyRadius,
+    Morphology,
+    SpeckSize,
+    RemoveSpecks,
+    Crop,
+    CropAspect,
+    CropX,
+    CropY,
+    CropWidth,
+    CropHeight,
+    CropApply,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+    RotateAngle,
+    RotateInterpolation,
+    RotateFit,
+    RotateFill,
+    RotateTransparent,
+    Rotate,
+    Straighten,
+    DeskewMaxAngle,
+    Deskew,
+    ResizeMode,
+    ResizeWidth,
+    ResizeHeight,
+    ResizePercent,
+    ResizeLongEdge,
+    ResizeKeepAspect,
+    ResizeFilter,
+    Resize,
+    CanvasWidth,
+    CanvasHeight,
+    CanvasAnchor,
+    CanvasFill,
+    CanvasTransparent,
+    CanvasSize,
+    BorderWidth,
+    BorderColor,
+    Border,
+    TrimMode,
+    TrimTolerance,
+    TrimMargin,
+    Trim,
+    Perspective,
+    PerspectiveAspect,
+    PerspectiveInterpolation,
+    PerspectiveApply,
+    ZoomFit,
+    Zoom100,
+    Zoom200,
+    ZoomIn,
+    ZoomOut,
+    CompareMode,
+    CompareReference,
+    CompareStep,
+    Compare,
+    PickTarget,
+    Eyedropper,
+    PickedNeutral,
 }
 
 #[derive(Debug)]
 pub struct Editor {
     image: Option<Image>,
+    /// Original of an image decoded in Rust, which has no image element.
+    decoded_image: Option<ImageDataWrapper>,
     renderer: Renderer,
     image_data: ImageDataList,
     display_elements: HashMap<EditorElement, Box<dyn DisplayElement>>,
     mode_manager: ModeManager,
+    tool: Tool,
+    /// State of the active tool, when it draws over the image.
+    tool_overlay: Option<Rc<Mutex<dyn ToolOverlay>>>,
 }
 
 impl Editor {
     pub fn new(renderer: Renderer) -> Result<Self> {
         Ok(Self {
             image: None,
+            decoded_image: None,
             renderer,
             image_data: ImageDataList::new(),
             display_elements: Self::make_display_elements()?,
             mode_manager: ModeManager::new(),
+            tool: Tool::None,
+            tool_overlay: None,
         })
     }
 
@@ -66,6 +222,19 @@ impl Editor {
             EditorElement::Save,
             Box::new(Button::new_from_id("save")?)
         );
+        display_elements.insert(
+            EditorElement::Copy,
+            Box::new(Button::new_from_id("copy")?)
+        );
+        // The checkbox stays hidden unless the decoder is compiled in.
+        #[cfg(feature = "native-decode")]
+        {
+            browser::label("native_decode_label")?.set_hidden(false);
+            display_elements.insert(
+                EditorElement::NativeDecode,
+                Box::new(Input::new_from_id("native_decode")?)
+            );
+        }
         display_elements.insert(
             EditorElement::Undo,
             Box::new(Button::new_from_id("back")?)
@@ -74,6 +243,490 @@ impl Editor {
             EditorElement::Redo,
             Box::new(Button::new_from_id("forward")?)
         );
+        display_elements.insert(
+            EditorElement::HistogramEqualization,
+            Box::new(Button::new_from_id("histogram_equalization")?)
+        );
+        display_elements.insert(
+            EditorElement::Clahe,
+            Box::new(Button::new_from_id("clahe")?)
+        );
+        display_elements.insert(
+            EditorElement::ClaheTileGrid,
+            Box::new(Input::new_from_id("clahe_tile_grid")?)
+        );
+        display_elements.insert(
+            EditorElement::ClaheClipLimit,
+            Box::new(Input::new_from_id("clahe_clip_limit")?)
+        );
+        display_elements.insert(
+            EditorElement::GrayWorld,
+            Box::new(Button::new_from_id("gray_world")?)
+        );
+        display_elements.insert(
+            EditorElement::WhitePatch,
+            Box::new(Button::new_from_id("white_patch")?)
+        );
+        display_elements.insert(
+            EditorElement::PercentileStretch,
+            Box::new(Button::new_from_id("percentile_stretch")?)
+        );
+        display_elements.insert(
+            EditorElement::StretchPercent,
+            Box::new(Input::new_from_id("stretch_percent")?)
+        );
+        display_elements.insert(
+            EditorElement::NeutralPick,
+            Box::new(Button::new_from_id("neutral_pick")?)
+        );
+        display_elements.insert(
+            EditorElement::Invert,
+            Box::new(Button::new_from_id("invert")?)
+        );
+        display_elements.insert(
+            EditorElement::InvertPreserveAlpha,
+            Box::new(Input::new_from_id("invert_preserve_alpha")?)
+        );
+        display_elements.insert(
+            EditorElement::Sepia,
+            Box::new(Button::new_from_id("sepia")?)
+        );
+        display_elements.insert(
+            EditorElement::SepiaStrength,
+            Box::new(Input::new_from_id("sepia_strength")?)
+        );
+        display_elements.insert(
+            EditorElement::Posterize,
+            Box::new(Button::new_from_id("posterize")?)
+        );
+        display_elements.insert(
+            EditorElement::PosterizeLevels,
+            Box::new(Input::new_from_id("posterize_levels")?)
+        );
+        display_elements.insert(
+            EditorElement::Solarize,
+            Box::new(Button::new_from_id("solarize")?)
+        );
+        display_elements.insert(
+            EditorElement::SolarizeThreshold,
+            Box::new(Input::new_from_id("solarize_threshold")?)
+        );
+        display_elements.insert(
+            EditorElement::Duotone,
+            Box::new(Button::new_from_id("duotone")?)
+        );
+        display_elements.insert(
+            EditorElement::DuotoneDark,
+            Box::new(Input::new_from_id("duotone_dark")?)
+        );
+        display_elements.insert(
+            EditorElement::DuotoneLight,
+            Box::new(Input::new_from_id("duotone_light")?)
+        );
+        display_elements.insert(
+            EditorElement::Blur,
+            Box::new(Button::new_from_id("blur")?)
+        );
+        display_elements.insert(
+            EditorElement::BlurKind,
+            Box::new(Select::new_from_id("blur_kind")?)
+        );
+        display_elements.insert(
+            EditorElement::BlurRadius,
+            Box::new(Input::new_from_id("blur_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::BlurEdgeMode,
+            Box::new(Select::new_from_id("blur_edge_mode")?)
+        );
+        display_elements.insert(
+            EditorElement::Sharpen,
+            Box::new(Button::new_from_id("sharpen")?)
+        );
+        display_elements.insert(
+            EditorElement::SharpenPreview,
+            Box::new(Button::new_from_id("sharpen_preview")?)
+        );
+        display_elements.insert(
+            EditorElement::UnsharpMask,
+            Box::new(Button::new_from_id("unsharp_mask")?)
+        );
+        display_elements.insert(
+            EditorElement::UnsharpMaskPreview,
+            Box::new(Button::new_from_id("unsharp_mask_preview")?)
+        );
+        display_elements.insert(
+            EditorElement::UnsharpAmount,
+            Box::new(Input::new_from_id("unsharp_amount")?)
+        );
+        display_elements.insert(
+            EditorElement::UnsharpRadius,
+            Box::new(Input::new_from_id("unsharp_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::UnsharpThreshold,
+            Box::new(Input::new_from_id("unsharp_threshold")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelPreset,
+            Box::new(Select::new_from_id("kernel_preset")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelText,
+            Box::new(TextArea::new_from_id("kernel_text")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelDivisor,
+            Box::new(Input::new_from_id("kernel_divisor")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelBias,
+            Box::new(Input::new_from_id("kernel_bias")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelChannels,
+            Box::new(Select::new_from_id("kernel_channels")?)
+        );
+        display_elements.insert(
+            EditorElement::KernelEdgeMode,
+            Box::new(Select::new_from_id("kernel_edge_mode")?)
+        );
+        display_elements.insert(
+            EditorElement::Convolution,
+            Box::new(Button::new_from_id("convolution")?)
+        );
+        display_elements.insert(
+            EditorElement::ConvolutionPreview,
+            Box::new(Button::new_from_id("convolution_preview")?)
+        );
+        display_elements.insert(
+            EditorElement::GradientOperator,
+            Box::new(Select::new_from_id("gradient_operator")?)
+        );
+        display_elements.insert(
+            EditorElement::GradientDirection,
+            Box::new(Input::new_from_id("gradient_direction")?)
+        );
+        display_elements.insert(
+            EditorElement::Gradient,
+            Box::new(Button::new_from_id("gradient")?)
+        );
+        display_elements.insert(
+            EditorElement::LogRadius,
+            Box::new(Input::new_from_id("log_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::LogThreshold,
+            Box::new(Input::new_from_id("log_threshold")?)
+        );
+        display_elements.insert(
+            EditorElement::LaplacianOfGaussian,
+            Box::new(Button::new_from_id("laplacian_of_gaussian")?)
+        );
+        display_elements.insert(
+            EditorElement::CannyRadius,
+            Box::new(Input::new_from_id("canny_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::CannyLow,
+            Box::new(Input::new_from_id("canny_low")?)
+        );
+        display_elements.insert(
+            EditorElement::CannyHigh,
+            Box::new(Input::new_from_id("canny_high")?)
+        );
+        display_elements.insert(
+            EditorElement::Canny,
+            Box::new(Button::new_from_id("canny")?)
+        );
+        display_elements.insert(
+            EditorElement::MedianRadius,
+            Box::new(Input::new_from_id("median_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::Median,
+            Box::new(Button::new_from_id("median")?)
+        );
+        display_elements.insert(
+            EditorElement::BilateralRadius,
+            Box::new(Input::new_from_id("bilateral_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::BilateralSigmaColor,
+            Box::new(Input::new_from_id("bilateral_sigma_color")?)
+        );
+        display_elements.insert(
+            EditorElement::Bilateral,
+            Box::new(Button::new_from_id("bilateral")?)
+        );
+        display_elements.insert(
+            EditorElement::NlmSearchRadius,
+            Box::new(Input::new_from_id("nlm_search_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::NlmStrength,
+            Box::new(Input::new_from_id("nlm_strength")?)
+        );
+        display_elements.insert(
+            EditorElement::NonLocalMeans,
+            Box::new(Button::new_from_id("non_local_means")?)
+        );
+        display_elements.insert(
+            EditorElement::MorphologyOperation,
+            Box::new(Select::new_from_id("morphology_operation")?)
+        );
+        display_elements.insert(
+            EditorElement::MorphologyElement,
+            Box::new(Select::new_from_id("morphology_element")?)
+        );
+        display_elements.insert(
+            EditorElement::MorphologyRadius,
+            Box::new(Input::new_from_id("morphology_radius")?)
+        );
+        display_elements.insert(
+            EditorElement::Morphology,
+            Box::new(Button::new_from_id("morphology")?)
+        );
+        display_elements.insert(
+            EditorElement::SpeckSize,
+            Box::new(Input::new_from_id("speck_size")?)
+        );
+        display_elements.insert(
+            EditorElement::RemoveSpecks,
+            Box::new(Button::new_from_id("remove_specks")?)
+        );
+        display_elements.insert(
+            EditorElement::Crop,
+            Box::new(Button::new_from_id("crop")?)
+        );
+        display_elements.insert(
+            EditorElement::CropAspect,
+            Box::new(Select::new_from_id("crop_aspect")?)
+        );
+        display_elements.insert(
+            EditorElement::CropX,
+            Box::new(Input::new_from_id("crop_x")?)
+        );
+        display_elements.insert(
+            EditorElement::CropY,
+            Box::new(Input::new_from_id("crop_y")?)
+        );
+        display_elements.insert(
+            EditorElement::CropWidth,
+            Box::new(Input::new_from_id("crop_width")?)
+        );
+        display_elements.insert(
+            EditorElement::CropHeight,
+            Box::new(Input::new_from_id("crop_height")?)
+        );
+        display_elements.insert(
+            EditorElement::CropApply,
+            Box::new(Button::new_from_id("crop_apply")?)
+        );
+        display_elements.insert(
+            EditorElement::Rotate90,
+            Box::new(Button::new_from_id("rotate_90")?)
+        );
+        display_elements.insert(
+            EditorElement::Rotate180,
+            Box::new(Button::new_from_id("rotate_180")?)
+        );
+        display_elements.insert(
+            EditorElement::Rotate270,
+            Box::new(Button::new_from_id("rotate_270")?)
+        );
+        display_elements.insert(
+            EditorElement::FlipHorizontal,
+            Box::new(Button::new_from_id("flip_horizontal")?)
+        );
+        display_elements.insert(
+            EditorElement::FlipVertical,
+            Box::new(Button::new_from_id("flip_vertical")?)
+        );
+        display_elements.insert(
+            EditorElement::RotateAngle,
+            Box::new(Input::new_from_id("rotate_angle")?)
+        );
+        display_elements.insert(
+            EditorElement::RotateInterpolation,
+            Box::new(Select::new_from_id("rotate_interpolation")?)
+        );
+        display_elements.insert(
+            EditorElement::RotateFit,
+            Box::new(Select::new_from_id("rotate_fit")?)
+        );
+        display_elements.insert(
+            EditorElement::RotateFill,
+            Box::new(Input::new_from_id("rotate_fill")?)
+        );
+        display_elements.insert(
+            EditorElement::RotateTransparent,
+            Box::new(Input::new_from_id("rotate_transparent")?)
+        );
+        display_elements.insert(
+            EditorElement::Rotate,
+            Box::new(Button::new_from_id("rotate")?)
+        );
+        display_elements.insert(
+            EditorElement::Straighten,
+            Box::new(Button::new_from_id("straighten")?)
+        );
+        display_elements.insert(
+            EditorElement::DeskewMaxAngle,
+            Box::new(Input::new_from_id("deskew_max_angle")?)
+        );
+        display_elements.insert(
+            EditorElement::Deskew,
+            Box::new(Button::new_from_id("deskew")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeMode,
+            Box::new(Select::new_from_id("resize_mode")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeWidth,
+            Box::new(Input::new_from_id("resize_width")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeHeight,
+            Box::new(Input::new_from_id("resize_height")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizePercent,
+            Box::new(Input::new_from_id("resize_percent")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeLongEdge,
+            Box::new(Input::new_from_id("resize_long_edge")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeKeepAspect,
+            Box::new(Input::new_from_id("resize_keep_aspect")?)
+        );
+        display_elements.insert(
+            EditorElement::ResizeFilter,
+            Box::new(Select::new_from_id("resize_filter")?)
+        );
+        display_elements.insert(
+            EditorElement::Resize,
+            Box::new(Button::new_from_id("resize")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasWidth,
+            Box::new(Input::new_from_id("canvas_width")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasHeight,
+            Box::new(Input::new_from_id("canvas_height")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasAnchor,
+            Box::new(Select::new_from_id("canvas_anchor")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasFill,
+            Box::new(Input::new_from_id("canvas_fill")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasTransparent,
+            Box::new(Input::new_from_id("canvas_transparent")?)
+        );
+        display_elements.insert(
+            EditorElement::CanvasSize,
+            Box::new(Button::new_from_id("canvas_size")?)
+        );
+        display_elements.insert(
+            EditorElement::BorderWidth,
+            Box::new(Input::new_from_id("border_width")?)
+        );
+        display_elements.insert(
+            EditorElement::BorderColor,
+            Box::new(Input::new_from_id("border_color")?)
+        );
+        display_elements.insert(
+            EditorElement::Border,
+            Box::new(Button::new_from_id("border")?)
+        );
+        display_elements.insert(
+            EditorElement::TrimMode,
+            Box::new(Select::new_from_id("trim_mode")?)
+        );
+        display_elements.insert(
+            EditorElement::TrimTolerance,
+            Box::new(Input::new_from_id("trim_tolerance")?)
+        );
+        display_elements.insert(
+            EditorElement::TrimMargin,
+            Box::new(Input::new_from_id("trim_margin")?)
+        );
+        display_elements.insert(
+            EditorElement::Trim,
+            Box::new(Button::new_from_id("trim")?)
+        );
+        display_elements.insert(
+            EditorElement::Perspective,
+            Box::new(Button::new_from_id("perspective")?)
+        );
+        display_elements.insert(
+            EditorElement::PerspectiveAspect,
+            Box::new(Select::new_from_id("perspective_aspect")?)
+        );
+        display_elements.insert(
+            EditorElement::PerspectiveInterpolation,
+            Box::new(Select::new_from_id("perspective_interpolation")?)
+        );
+        display_elements.insert(
+            EditorElement::PerspectiveApply,
+            Box::new(Button::new_from_id("perspective_apply")?)
+        );
+        display_elements.insert(
+            EditorElement::ZoomFit,
+            Box::new(Button::new_from_id("zoom_fit")?)
+        );
+        display_elements.insert(
+            EditorElement::Zoom100,
+            Box::new(Button::new_from_id("zoom_100")?)
+        );
+        display_elements.insert(
+            EditorElement::Zoom200,
+            Box::new(Button::new_from_id("zoom_200")?)
+        );
+        display_elements.insert(
+            EditorElement::ZoomIn,
+            Box::new(Button::new_from_id("zoom_in")?)
+        );
+        display_elements.insert(
+            EditorElement::ZoomOut,
+            Box::new(Button::new_from_id("zoom_out")?)
+        );
+        display_elements.insert(
+            EditorElement::CompareMode,
+            Box::new(Select::new_from_id("compare_mode")?)
+        );
+        display_elements.insert(
+            EditorElement::CompareReference,
+            Box::new(Select::new_from_id("compare_reference")?)
+        );
+        display_elements.insert(
+            EditorElement::CompareStep,
+            Box::new(Input::new_from_id("compare_step")?)
+        );
+        display_elements.insert(
+            EditorElement::Compare,
+            Box::new(Button::new_from_id("compare")?)
+        );
+        display_elements.insert(
+            EditorElement::PickTarget,
+            Box::new(Select::new_from_id("pick_target")?)
+        );
+        display_elements.insert(
+            EditorElement::Eyedropper,
+            Box::new(Button::new_from_id("eyedropper")?)
+        );
+        display_elements.insert(
+            EditorElement::PickedNeutral,
+            Box::new(Button::new_from_id("picked_neutral")?)
+        );
         Ok(display_elements)
     }
 
@@ -111,6 +764,25 @@ impl Editor {
         self.image_data.get_image_data()
     }
 
+    /// Canvas with the image as it was loaded, before any filter.
+    pub fn original_canvas(&self) -> Result<Option<Canvas>> {
+        match (&self.image, &self.decoded_image) {
+            (Some(image), _) => Canvas::new_from_image(image).map(Some),
+            (None, Some(image_data)) => Canvas::new_from_image_data(image_data).map(Some),
+            (None, None) => Ok(None),
+        }
+    }
+
+    /// History entry `index`, counted from the oldest one kept.
+    pub fn history_image_data(&self, index: usize) -> Option<&ImageDataWrapper> {
+        self.image_data.get(index)
+    }
+
+    /// Index of the current history entry and the number of entries.
+    pub fn history_position(&self) -> (usize, usize) {
+        (self.image_data.current_index(), self.image_data.len())
+    }
+
     pub fn get_image_data_mut(&mut self) -> Option<&mut [u8]> {
         self.image_data.get_image_data_inner_mut()
     }
@@ -119,8 +791,98 @@ impl Editor {
         !self.image_data.is_empty()
     }
 
+    pub fn tool(&self) -> Tool {
+        self.tool
+    }
+
+    pub fn set_tool(&mut self, tool: Tool) -> Result<()> {
+        self.tool = tool;
+        self.tool_overlay = None;
+        let canvas = Canvas::new_from_element(browser::canvas()?);
+        canvas.set_class("tool_active", tool != Tool::None)
+    }
+
+    /// Maps a canvas position to a pixel of the current image.
+    pub fn canvas_to_image_position(&self, x: f64, y: f64) -> Option<(u32, u32)> {
+        let image_data = self.image_data.get_image_data()?;
+        self.renderer.canvas_to_image_position(x, y, image_data.size())
+    }
+
+    /// Canvas pixels per image pixel with the current zoom.
+    pub fn zoom_scale(&self) -> Option<f64> {
+        let image_data = self.image_data.get_image_data()?;
+        Some(self.renderer.zoom_scale(image_data.size()))
+    }
+
+    /// Zoom chosen by the user, or `None` when the image is fitted into the canvas.
+    pub fn viewport_zoom(&self) -> Option<f64> {
+        self.renderer.viewport().zoom()
+    }
+
+    /// Sets the zoom around the canvas center, or fits the image for `None`.
+    pub fn set_zoom(&mut self, zoom: Option<f64>) {
+        self.renderer.set_zoom(zoom);
+    }
+
+    /// Sets the zoom keeping the image point under `anchor` in place.
+    pub fn zoom_at(&mut self, zoom: f64, anchor: (f64, f64)) {
+        if let Some(image_data) = self.image_data.get_image_data() {
+            self.renderer.zoom_at(image_data.size(), zoom, anchor);
+        }
+    }
+
+    pub fn pan_by(&mut self, delta: (f64, f64)) {
+        if let Some(image_data) = self.image_data.get_image_data() {
+            self.renderer.pan_by(image_data.size(), delta);
+        }
+    }
+
+    /// Lets the active tool draw its overlay again with the current canvas size and zoom.
+    fn set_tool_overlay(&mut self, overlay: Rc<Mutex<dyn ToolOverlay>>) {
+        self.tool_overlay = Some(overlay);
+    }
+
+    /// Leaves the active tool and drops its state, as the image it works on was replaced.
+    /// Called whenever the history entry or the original image changes.
+    pub fn reset_tool(&mut self) -> Result<()> {
+        if let Some(overlay) = self.tool_overlay.take() {
+            lock_state(&overlay)?.reset()?;
+        }
+        self.set_tool(Tool::None)
+    }
+
+    /// Draws the current image again, with the overlay of the active tool if it has one.
+    pub fn redraw_with_tool(&self) -> Result<()> {
+        match &self.tool_overlay {
+            Some(overlay) => lock_state(overlay)?.redraw(self),
+            None => self.draw_image_data(),
+        }
+    }
+
+    /// Lets `draw` paint over the canvas with the current image and the renderer.
+    pub fn draw_over_image<F>(&self, draw: F) -> Result<()>
+    where
+        F: FnOnce(&ImageDataWrapper, &Renderer) -> Result<()>,
+    {
+        if let Some(image_data) = self.image_data.get_image_data() {
+            draw(image_data, &self.renderer)
+        } else {
+            log!("No image data");
+            Ok(())
+        }
+    }
+
     pub fn set_image(&mut self, image: Image) {
         self.image = Some(image);
+        self.decoded_image = None;
+    }
+
+    /// Sets an image decoded in Rust as the new original and pushes it to the history.
+    #[cfg(feature = "native-decode")]
+    pub fn set_decoded_image(&mut self, image_data: ImageDataWrapper) {
+        self.image = None;
+        self.decoded_image = Some(image_data.clone());
+        self.image_data.push(image_data);
     }
 
     pub fn setup_image_data(&mut self) -> Result<()> {
@@ -145,6 +907,14 @@ impl Editor {
         self.renderer.update_canvas_size()
     }
 
+    pub fn pixel_ratio(&self) -> f64 {
+        self.renderer.pixel_ratio()
+    }
+
+    pub fn push_image_data(&mut self, image_data: ImageDataWrapper) {
+        self.image_data.push(image_data);
+    }
+
     pub fn clone_push(&mut self) {
         self.image_data.clone_push();
     }
@@ -169,9 +939,9 @@ impl Editor {
         Ok(())
     }
 
-    pub async fn draw_image_data(&self) -> Result<()> {
+    pub fn draw_image_data(&self) -> Result<()> {
         if let Some(image_data) = self.image_data.get_image_data() {
-            self.renderer.draw_image_data_fit_canvas(&image_data).await?;
+            self.renderer.draw_image_data_fit_canvas(image_data)?;
         }
         Ok(())
     }
@@ -187,6 +957,10 @@ impl Editor {
                     EditorElement::InputLabel | EditorElement::Input => {
                         element.set_disabled(false);
                     },
+                    #[cfg(feature = "native-decode")]
+                    EditorElement::NativeDecode => {
+                        element.set_disabled(false);
+                    },
                     EditorElement::Redo => {
                         if !self.image_data.is_last() {
                             element.set_disabled(false);
@@ -208,6 +982,48 @@ impl Editor {
     }
 }
 
+/// Locks the state a tool shares between its event closures.
+fn lock_state<T: ?Sized>(state: &Rc<Mutex<T>>) -> Result<MutexGuard<'_, T>> {
+    state.lock().map_err(|err| anyhow::anyhow!("{:#?}", err))
+}
+
+/// State of a tool that draws over the image.
+trait ToolOverlay: std::fmt::Debug {
+    /// Draws the image and the overlay again, after the canvas was resized or zoomed.
+    fn redraw(&mut self, editor: &Editor) -> Result<()>;
+
+    /// Drops the selection and the cached image, which no longer match a replaced image.
+    fn reset(&mut self) -> Result<()>;
+}
+
+/// Current image drawn once into an offscreen canvas, so a tool can draw it under its
+/// overlay on every pointer move.
+#[derive(Debug, Default)]
+struct BaseCanvas(Option<Canvas>);
+
+impl BaseCanvas {
+    pub fn set(&mut self, image_data: &ImageDataWrapper) -> Result<()> {
+        self.0 = Some(Canvas::new_from_image_data(image_data)?);
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    pub fn get(&self) -> Option<&Canvas> {
+        self.0.as_ref()
+    }
+
+    /// Draws the cached image with the current zoom, if there is one.
+    pub fn draw(&self, renderer: &Renderer) -> Result<()> {
+        if let Some(canvas) = &self.0 {
+            renderer.draw_canvas_in_view(canvas)?;
+        }
+        Ok(())
+    }
+}
+
 pub fn setup() -> Result<()> {
     let editor = Rc::new(
         Mutex::new(Editor::new(engine::Renderer::new()?
@@ -217,6 +1033,25 @@ pub fn setup() -> Result<()> {
     binarization::setup_binarization_event(editor.clone())?;
     save::setup_save_event(editor.clone())?;
     back_and_forward::setup_back_and_forward_event(editor.clone())?;
+    contrast::setup_contrast_event(editor.clone())?;
+    white_balance::setup_white_balance_event(editor.clone())?;
+    effect::setup_effect_event(editor.clone())?;
+    blur::setup_blur_event(editor.clone())?;
+    sharpen::setup_sharpen_event(editor.clone())?;
+    convolution::setup_convolution_event(editor.clone())?;
+    edge_detection::setup_edge_detection_event(editor.clone())?;
+    denoise::setup_denoise_event(editor.clone())?;
+    morphology::setup_morphology_event(editor.clone())?;
+    crop::setup_crop_event(editor.clone())?;
+    orientation::setup_orientation_event(editor.clone())?;
+    rotate::setup_rotate_event(editor.clone())?;
+    resize::setup_resize_event(editor.clone())?;
+    canvas_size::setup_canvas_size_event(editor.clone())?;
+    trim::setup_trim_event(editor.clone())?;
+    perspective::setup_perspective_event(editor.clone())?;
+    viewport::setup_viewport_event(editor.clone())?;
+    compare::setup_compare_event(editor.clone())?;
+    inspector::setup_inspector_event(editor.clone())?;
     Editor::lock(&editor)?.set_disabled(true);
     Editor::lock(&editor)?.set_disabled(false);
     Ok(())
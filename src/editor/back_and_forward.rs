@@ -7,13 +7,13 @@ use crate::browser;
 use crate::engine::Button;
 use super::Editor;
 
-async fn forward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
+fn forward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     if !Editor::try_run_id(&editor, id) {
         forward_event(editor, id)?;
         return Ok(());
     }
     let mut editor = Editor::lock(&editor)?;
-    let result = editor.draw_image_data().await;
+    let result = editor.draw_image_data();
     editor.set_disabled(false);
     editor.to_idle();
     result
@@ -22,7 +22,7 @@ async fn forward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
 fn forward_event(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     browser::set_callback_once(move || {
         browser::spawn_local(async move {
-            if let Err(err) = forward_event_inner(editor, id).await {
+            if let Err(err) = forward_event_inner(editor, id) {
                 error!("{:#?}", err);
             }
         });
@@ -38,6 +38,7 @@ fn setup_forward_event_closure(editor: Rc<Mutex<Editor>>) -> Result<()> {
     {
         let mut editor = Editor::lock(&editor)?;
         if let Some(_) = editor.redo() {
+            editor.reset_tool()?;
             editor.set_disabled(true);
         } else {
             editor.to_idle();
@@ -63,13 +64,13 @@ pub fn setup_forward_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
     Ok(())
 }
 
-async fn backward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
+fn backward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     if !Editor::try_run_id(&editor, id) {
         backward_event(editor, id)?;
         return Ok(());
     }
     let mut editor = Editor::lock(&editor)?;
-    let result = editor.draw_image_data().await;
+    let result = editor.draw_image_data();
     editor.set_disabled(false);
     editor.to_idle();
     result
@@ -78,7 +79,7 @@ async fn backward_event_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()>
 fn backward_event(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     browser::set_callback_once(move || {
         browser::spawn_local(async move {
-            if let Err(err) = backward_event_inner(editor, id).await {
+            if let Err(err) = backward_event_inner(editor, id) {
                 error!("{:#?}", err);
             }
         });
@@ -94,6 +95,7 @@ fn setup_backward_event_closure(editor: Rc<Mutex<Editor>>) -> Result<()> {
     {
         let mut editor = Editor::lock(&editor)?;
         if let Some(_) = editor.undo() {
+            editor.reset_tool()?;
             editor.set_disabled(true);
         } else {
             editor.to_idle();
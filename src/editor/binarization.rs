@@ -33,13 +33,13 @@ fn binarization_step(image_data: &mut [u8], temp: &mut Temp, step: usize) -> boo
     false
 }
 
-async fn final_step_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
+fn final_step_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     if !Editor::try_run_id(&editor, id) {
         final_step(editor, id)?;
         return Ok(());
     }
     let mut editor = Editor::lock(&editor)?;
-    let result = editor.draw_image_data().await;
+    let result = editor.draw_image_data();
     editor.set_disabled(false);
     editor.to_idle();
     result
@@ -48,7 +48,7 @@ async fn final_step_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
 fn final_step(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
     browser::set_callback_once(move || {
         browser::spawn_local(async move {
-            if let Err(err) = final_step_inner(editor, id).await {
+            if let Err(err) = final_step_inner(editor, id) {
                 error!("{:#?}", err);
             }
         });
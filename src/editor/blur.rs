@@ -0,0 +1,19 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::{Input, Select};
+use crate::filter::{Blur, BlurKind, EdgeMode};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_blur_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor, "blur", Rc::new(|image_data| {
+        let kind: BlurKind = Select::new_from_id("blur_kind")?.value().parse()?;
+        let radius = Input::new_from_id("blur_radius")?.value_as_number()?;
+        let edge_mode: EdgeMode = Select::new_from_id("blur_edge_mode")?.value().parse()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Blur::new(
+            kind, image_data.data(), width, height, radius as u32, edge_mode)?))
+    }))
+}
@@ -0,0 +1,32 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::{Input, Select};
+use crate::filter::{CanvasAnchor, CanvasSize, parse_hex_color};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_canvas_size_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "canvas_size", Rc::new(|image_data| {
+        let canvas_width = Input::new_from_id("canvas_width")?.value_as_number()?;
+        let canvas_height = Input::new_from_id("canvas_height")?.value_as_number()?;
+        let anchor: CanvasAnchor = Select::new_from_id("canvas_anchor")?.value().parse()?;
+        let fill = if Input::new_from_id("canvas_transparent")?.checked() {
+            [0; 4]
+        } else {
+            let [r, g, b] = parse_hex_color(&Input::new_from_id("canvas_fill")?.value())?;
+            [r, g, b, 255]
+        };
+        let (width, height) = image_data.size();
+        Ok(Box::new(CanvasSize::new(
+            image_data.data(), width, height, canvas_width as u32, canvas_height as u32, anchor, fill)?))
+    }))?;
+    setup_filter_event(editor, "border", Rc::new(|image_data| {
+        let border_width = Input::new_from_id("border_width")?.value_as_number()?;
+        let [r, g, b] = parse_hex_color(&Input::new_from_id("border_color")?.value())?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(CanvasSize::border(
+            image_data.data(), width, height, border_width as u32, [r, g, b, 255])?))
+    }))
+}
@@ -0,0 +1,240 @@
+use std::rc::Rc;
+use std::str::FromStr;
+use std::sync::Mutex;
+use anyhow::{Error, Result, anyhow};
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Comparison, Input, Select, Text};
+use super::{BaseCanvas, Editor, Tool, ToolOverlay, lock_state};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CompareMode {
+    Split,
+    SideBySide,
+    Hold,
+}
+
+impl FromStr for CompareMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "split" => Ok(CompareMode::Split),
+            "side_by_side" => Ok(CompareMode::SideBySide),
+            "hold" => Ok(CompareMode::Hold),
+            _ => Err(anyhow!("Unknown compare mode {:#?}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CompareState {
+    current: BaseCanvas,
+    reference: Option<Canvas>,
+    /// Position of the split line as a fraction of the canvas width.
+    split: f64,
+    dragging: bool,
+    holding: bool,
+}
+
+impl ToolOverlay for CompareState {
+    fn redraw(&mut self, editor: &Editor) -> Result<()> {
+        draw(editor, self)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        *self = Self::default();
+        Text::new_from_id("compare_report")?.set_text("");
+        Ok(())
+    }
+}
+
+/// Draws the reference chosen in the UI, and describes it in the report.
+fn load_reference(editor: &Editor, state: &mut CompareState) -> Result<()> {
+    let (current, len) = editor.history_position();
+    let step = match Select::new_from_id("compare_reference")?.value().as_str() {
+        "original" => None,
+        "previous" => current.checked_sub(1),
+        "step" => {
+            let step = Input::new_from_id("compare_step")?.value_as_number()?;
+            Some((step.max(1.0) as usize - 1).min(len.saturating_sub(1)))
+        },
+        value => return Err(anyhow!("Unknown compare reference {:#?}", value)),
+    };
+    let report = Text::new_from_id("compare_report")?;
+    match step.and_then(|step| Some((step, editor.history_image_data(step)?))) {
+        Some((step, image_data)) => {
+            state.reference = Some(Canvas::new_from_image_data(image_data)?);
+            report.set_text(&format!("Step {} of {} against step {}", current + 1, len, step + 1));
+        },
+        None => {
+            let original = editor.original_canvas()?.ok_or_else(|| anyhow!("No image loaded"))?;
+            state.reference = Some(original);
+            report.set_text(&format!("Step {} of {} against the original", current + 1, len));
+        },
+    }
+    Ok(())
+}
+
+fn draw(editor: &Editor, state: &CompareState) -> Result<()> {
+    let (current, reference) = match (state.current.get(), &state.reference) {
+        (Some(current), Some(reference)) => (current, reference),
+        _ => return Ok(()),
+    };
+    let mode: CompareMode = Select::new_from_id("compare_mode")?.value().parse()?;
+    editor.draw_over_image(|_, renderer| {
+        let comparison = match mode {
+            CompareMode::Split => Comparison::Split(state.split * renderer.size().0 as f64),
+            CompareMode::SideBySide => Comparison::SideBySide,
+            CompareMode::Hold if state.holding => Comparison::Reference,
+            CompareMode::Hold => return renderer.draw_canvas_in_view(current),
+        };
+        renderer.draw_comparison(current, reference, comparison)
+    })
+}
+
+fn compare_button_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CompareState>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let overlay = state.clone();
+    let mut state = lock_state(state)?;
+    if editor.tool() == Tool::Compare {
+        state.reset()?;
+        editor.set_tool(Tool::None)?;
+        return editor.draw_image_data();
+    }
+    let image_data = if let Some(image_data) = editor.get_image_data() {
+        image_data
+    } else {
+        log!("No image data");
+        return Ok(());
+    };
+    state.current.set(image_data)?;
+    state.split = 0.5;
+    load_reference(&editor, &mut state)?;
+    editor.set_tool(Tool::Compare)?;
+    editor.set_tool_overlay(overlay);
+    draw(&editor, &state)
+}
+
+fn setup_compare_button_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CompareState>>) -> Result<()> {
+    let button_element = Button::new_from_id("compare")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = compare_button_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+/// Redraws with the mode or reference changed in the UI.
+fn compare_option_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CompareState>>) -> Result<()> {
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Compare {
+        return Ok(());
+    }
+    let mut state = lock_state(state)?;
+    load_reference(&editor, &mut state)?;
+    draw(&editor, &state)
+}
+
+fn setup_compare_option_events(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CompareState>>) -> Result<()> {
+    for id in ["compare_mode", "compare_reference"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |_event: Event| {
+            if let Err(err) = compare_option_closure(&editor, &state) {
+                error!("{:#?}", err);
+            }
+        });
+        Select::new_from_id(id)?.add_event_listener_with_callback(&closure)?;
+        closure.forget();
+    }
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = compare_option_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+    Input::new_from_id("compare_step")?.set_onchange(&closure);
+    closure.forget();
+
+    Ok(())
+}
+
+fn compare_canvas_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CompareState>>,
+                          event_name: &str, event: &Event) -> Result<()> {
+    let mut state = lock_state(state)?;
+    if event_name != "pointerdown" && !state.dragging && !state.holding {
+        return Ok(());
+    }
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Compare {
+        return Ok(());
+    }
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let (x, _) = canvas.event_position(event)?;
+    let split = (x / canvas.size().0.max(1) as f64).clamp(0.0, 1.0);
+    match event_name {
+        "pointerdown" => {
+            match Select::new_from_id("compare_mode")?.value().parse()? {
+                CompareMode::Split => {
+                    state.split = split;
+                    state.dragging = true;
+                },
+                CompareMode::Hold => state.holding = true,
+                CompareMode::SideBySide => return Ok(()),
+            }
+        },
+        "pointermove" if state.dragging => state.split = split,
+        "pointermove" => return Ok(()),
+        _ => {
+            state.dragging = false;
+            state.holding = false;
+        },
+    }
+    draw(&editor, &state)
+}
+
+fn setup_compare_canvas_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CompareState>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["pointerdown", "pointermove", "pointerup", "pointerleave"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = compare_canvas_closure(&editor, &state, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+pub fn setup_compare_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let state = Rc::new(Mutex::new(CompareState::default()));
+    setup_compare_button_event(editor.clone(), state.clone())?;
+    setup_compare_option_events(editor.clone(), state.clone())?;
+    setup_compare_canvas_event(editor, state)?;
+    Ok(())
+}
@@ -0,0 +1,23 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::Input;
+use crate::filter::{HistogramEqualization, Clahe};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_contrast_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "histogram_equalization", Rc::new(|image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(HistogramEqualization::new(image_data.data(), width, height)))
+    }))?;
+    setup_filter_event(editor, "clahe", Rc::new(|image_data| {
+        let tile_grid = Input::new_from_id("clahe_tile_grid")?.value_as_number()?;
+        let clip_limit = Input::new_from_id("clahe_clip_limit")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Clahe::new(
+            image_data.data(), width, height, tile_grid as u32, clip_limit)?))
+    }))?;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Input, Select, TextArea};
+use crate::filter::{Kernel, KernelPreset, Convolution, ConvolutionOptions};
+use super::Editor;
+use super::filter::{setup_filter_event, FilterFactory};
+use super::preview::setup_preview_event;
+
+fn load_kernel_preset() -> Result<()> {
+    let preset: KernelPreset = Select::new_from_id("kernel_preset")?.value().parse()?;
+    let options = preset.options();
+    TextArea::new_from_id("kernel_text")?.set_value(&preset.kernel().to_string());
+    Input::new_from_id("kernel_divisor")?.set_value(&options.divisor.to_string());
+    Input::new_from_id("kernel_bias")?.set_value(&options.bias.to_string());
+    Ok(())
+}
+
+fn setup_kernel_preset_event() -> Result<()> {
+    let select_element = Select::new_from_id("kernel_preset")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = load_kernel_preset() {
+            error!("{:#?}", err);
+        }
+    });
+
+    select_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    load_kernel_preset()
+}
+
+fn convolution_factory() -> FilterFactory {
+    Rc::new(|image_data| {
+        let kernel: Kernel = TextArea::new_from_id("kernel_text")?.value().parse()?;
+        let options = ConvolutionOptions {
+            divisor: Input::new_from_id("kernel_divisor")?.value_as_number()?,
+            bias: Input::new_from_id("kernel_bias")?.value_as_number()?,
+            channels: Select::new_from_id("kernel_channels")?.value().parse()?,
+            edge_mode: Select::new_from_id("kernel_edge_mode")?.value().parse()?,
+        };
+        let (width, height) = image_data.size();
+        Ok(Box::new(Convolution::new(kernel, image_data.data(), width, height, options)?))
+    })
+}
+
+pub fn setup_convolution_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_kernel_preset_event()?;
+    setup_filter_event(editor.clone(), "convolution", convolution_factory())?;
+    setup_preview_event(editor, "convolution_preview", convolution_factory())?;
+    Ok(())
+}
@@ -0,0 +1,558 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::{Result, anyhow};
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, ImageDataWrapper, Input, Rect, Renderer, Select};
+use crate::filter::Crop;
+use super::{BaseCanvas, Editor, Tool, ToolOverlay, lock_state};
+use super::filter::run_filter;
+
+const HANDLE_SIZE: f64 = 8.0;
+const SELECTION_COLOR: &str = "#ff9800";
+const SHADE_COLOR: &str = "rgba(0, 0, 0, 0.5)";
+const POSITION_INPUT_IDS: [&str; 4] = ["crop_x", "crop_y", "crop_width", "crop_height"];
+
+/// Part of the selection grabbed by the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Handle {
+    Move,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Handle {
+    /// Horizontal and vertical side moved by the handle, -1 for left or top and 1 for right or bottom.
+    fn sides(self) -> (i8, i8) {
+        match self {
+            Handle::Move => (0, 0),
+            Handle::Left => (-1, 0),
+            Handle::Right => (1, 0),
+            Handle::Top => (0, -1),
+            Handle::Bottom => (0, 1),
+            Handle::TopLeft => (-1, -1),
+            Handle::TopRight => (1, -1),
+            Handle::BottomLeft => (-1, 1),
+            Handle::BottomRight => (1, 1),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Drag {
+    handle: Handle,
+    start: (f64, f64),
+    selection: Rect,
+}
+
+#[derive(Debug)]
+struct CropState {
+    /// Selection in image pixels.
+    selection: Rect,
+    aspect: Option<f64>,
+    drag: Option<Drag>,
+    image: BaseCanvas,
+}
+
+impl CropState {
+    fn new() -> Self {
+        Self {
+            selection: Rect::new(0.0, 0.0, 0.0, 0.0),
+            aspect: None,
+            drag: None,
+            image: BaseCanvas::default(),
+        }
+    }
+}
+
+impl ToolOverlay for CropState {
+    fn redraw(&mut self, editor: &Editor) -> Result<()> {
+        refresh(self, editor)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        *self = Self::new();
+        Ok(())
+    }
+}
+
+/// Parses `free` or a `width:height` ratio.
+pub fn parse_aspect(value: &str) -> Result<Option<f64>> {
+    if value == "free" {
+        return Ok(None);
+    }
+    let (width, height) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Unknown aspect ratio {:#?}", value))?;
+    let width: f64 = width.parse()?;
+    let height: f64 = height.parse()?;
+    if width <= 0.0 || height <= 0.0 {
+        return Err(anyhow!("Invalid aspect ratio {:#?}", value));
+    }
+    Ok(Some(width / height))
+}
+
+fn rect_from_points((x0, y0): (f64, f64), (x1, y1): (f64, f64)) -> Rect {
+    Rect::new(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs())
+}
+
+/// Largest rectangle with the aspect ratio centered inside `rect`.
+fn fit_aspect(rect: &Rect, aspect: Option<f64>) -> Rect {
+    let aspect = if let Some(aspect) = aspect {
+        aspect
+    } else {
+        return rect.clone();
+    };
+    let (width, height) = if rect.width > rect.height * aspect {
+        (rect.height * aspect, rect.height)
+    } else {
+        (rect.width, rect.width / aspect)
+    };
+    Rect::new(
+        rect.x + (rect.width - width) / 2.0,
+        rect.y + (rect.height - height) / 2.0,
+        width,
+        height,
+    )
+}
+
+fn image_rect((width, height): (u32, u32)) -> Rect {
+    Rect::new(0.0, 0.0, width as f64, height as f64)
+}
+
+/// Selection after moving `handle` of `selection` by `(dx, dy)` image pixels, kept inside the image.
+fn drag_selection(selection: &Rect, handle: Handle, (dx, dy): (f64, f64),
+                  aspect: Option<f64>, (width, height): (u32, u32)) -> Rect {
+    let (width, height) = (width as f64, height as f64);
+    let (left, top) = (selection.x, selection.y);
+    let (right, bottom) = (left + selection.width, top + selection.height);
+    if handle == Handle::Move {
+        return Rect::new(
+            (left + dx).clamp(0.0, width - selection.width),
+            (top + dy).clamp(0.0, height - selection.height),
+            selection.width,
+            selection.height,
+        );
+    }
+    // Each axis is a fixed anchor and a moving side, or both sides when the axis is untouched.
+    let (sides_x, sides_y) = handle.sides();
+    let (anchor_x, moving_x) = match sides_x {
+        -1 => (right, left + dx),
+        1 => (left, right + dx),
+        _ => (left, right),
+    };
+    let (anchor_y, moving_y) = match sides_y {
+        -1 => (bottom, top + dy),
+        1 => (top, bottom + dy),
+        _ => (top, bottom),
+    };
+    let (mut anchor_x, mut moving_x) = (anchor_x, moving_x.clamp(0.0, width));
+    let (mut anchor_y, mut moving_y) = (anchor_y, moving_y.clamp(0.0, height));
+    if let Some(aspect) = aspect {
+        let direction_x = (moving_x - anchor_x).signum();
+        let direction_y = (moving_y - anchor_y).signum();
+        let mut span_x = (moving_x - anchor_x).abs();
+        let mut span_y = (moving_y - anchor_y).abs();
+        match (sides_x, sides_y) {
+            (0, _) => {
+                let center = (left + right) / 2.0;
+                span_x = (span_y * aspect).min(2.0 * center.min(width - center));
+                span_y = span_x / aspect;
+                anchor_x = center - span_x / 2.0;
+                moving_x = center + span_x / 2.0;
+                moving_y = anchor_y + direction_y * span_y;
+            },
+            (_, 0) => {
+                let center = (top + bottom) / 2.0;
+                span_y = (span_x / aspect).min(2.0 * center.min(height - center));
+                span_x = span_y * aspect;
+                anchor_y = center - span_y / 2.0;
+                moving_y = center + span_y / 2.0;
+                moving_x = anchor_x + direction_x * span_x;
+            },
+            _ => {
+                if span_x > span_y * aspect {
+                    span_x = span_y * aspect;
+                } else {
+                    span_y = span_x / aspect;
+                }
+                moving_x = anchor_x + direction_x * span_x;
+                moving_y = anchor_y + direction_y * span_y;
+            },
+        }
+    }
+    rect_from_points((anchor_x, anchor_y), (moving_x, moving_y))
+}
+
+fn handle_points(rect: &Rect) -> [(Handle, f64, f64); 8] {
+    let (left, top) = (rect.x, rect.y);
+    let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+    let (center_x, center_y) = (rect.x + rect.width / 2.0, rect.y + rect.height / 2.0);
+    [
+        (Handle::TopLeft, left, top),
+        (Handle::Top, center_x, top),
+        (Handle::TopRight, right, top),
+        (Handle::Right, right, center_y),
+        (Handle::BottomRight, right, bottom),
+        (Handle::Bottom, center_x, bottom),
+        (Handle::BottomLeft, left, bottom),
+        (Handle::Left, left, center_y),
+    ]
+}
+
+/// Handle under the canvas position, `None` when outside of the selection.
+fn hit_handle(rect: &Rect, x: f64, y: f64, handle_size: f64) -> Option<Handle> {
+    let handle = handle_points(rect)
+        .iter()
+        .find(|(_, handle_x, handle_y)|
+            (x - handle_x).abs() <= handle_size && (y - handle_y).abs() <= handle_size)
+        .map(|(handle, _, _)| *handle);
+    if handle.is_some() {
+        return handle;
+    }
+    if x >= rect.x && x <= rect.x + rect.width && y >= rect.y && y <= rect.y + rect.height {
+        Some(Handle::Move)
+    } else {
+        None
+    }
+}
+
+fn draw_selection(state: &CropState, image_data: &ImageDataWrapper, renderer: &Renderer) -> Result<()> {
+    let size = image_data.size();
+    let fitted = renderer.get_image_rect(size);
+    state.image.draw(renderer)?;
+    let rect = renderer.image_to_canvas_rect(size, &state.selection);
+    let (right, bottom) = (rect.x + rect.width, rect.y + rect.height);
+    let shades = [
+        Rect::new(fitted.x, fitted.y, fitted.width, rect.y - fitted.y),
+        Rect::new(fitted.x, bottom, fitted.width, fitted.y + fitted.height - bottom),
+        Rect::new(fitted.x, rect.y, rect.x - fitted.x, rect.height),
+        Rect::new(right, rect.y, fitted.x + fitted.width - right, rect.height),
+    ];
+    for shade in shades.iter() {
+        renderer.fill_rect(shade, SHADE_COLOR);
+    }
+    renderer.stroke_rect(&rect, SELECTION_COLOR);
+    let handle_size = HANDLE_SIZE * renderer.pixel_ratio();
+    for (_, x, y) in handle_points(&rect) {
+        let handle = Rect::new(x - handle_size / 2.0, y - handle_size / 2.0, handle_size, handle_size);
+        renderer.fill_rect(&handle, SELECTION_COLOR);
+    }
+    Ok(())
+}
+
+fn update_position_inputs(selection: &Rect) -> Result<()> {
+    let values = [selection.x, selection.y, selection.width, selection.height];
+    for (id, value) in POSITION_INPUT_IDS.iter().zip(values) {
+        Input::new_from_id(id)?.set_value(&value.round().to_string());
+    }
+    Ok(())
+}
+
+/// Redraws the image with the selection and shows the selection in the inputs.
+fn refresh(state: &CropState, editor: &Editor) -> Result<()> {
+    editor.draw_over_image(|image_data, renderer| draw_selection(state, image_data, renderer))?;
+    update_position_inputs(&state.selection)
+}
+
+fn start_crop(state: &mut CropState, editor: &mut Editor, overlay: Rc<Mutex<CropState>>) -> Result<()> {
+    let image_data = if let Some(image_data) = editor.get_image_data() {
+        image_data
+    } else {
+        log!("No image data");
+        return Ok(());
+    };
+    state.aspect = parse_aspect(&Select::new_from_id("crop_aspect")?.value())?;
+    state.selection = fit_aspect(&image_rect(image_data.size()), state.aspect);
+    state.image.set(image_data)?;
+    editor.set_tool(Tool::Crop)?;
+    editor.set_tool_overlay(overlay);
+    refresh(state, editor)
+}
+
+/// Leaves the crop tool and draws the image without the selection.
+fn stop_crop(state: &mut CropState, editor: &mut Editor) -> Result<()> {
+    state.drag = None;
+    editor.set_tool(Tool::None)?;
+    editor.draw_over_image(|_, renderer| state.image.draw(renderer))?;
+    state.image.clear();
+    Ok(())
+}
+
+fn crop_button_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CropState>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let overlay = state.clone();
+    let mut state = lock_state(state)?;
+    if editor.tool() == Tool::Crop {
+        stop_crop(&mut state, &mut editor)
+    } else {
+        start_crop(&mut state, &mut editor, overlay)
+    }
+}
+
+fn setup_crop_button_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CropState>>) -> Result<()> {
+    let button_element = Button::new_from_id("crop")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = crop_button_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn crop_apply_closure(editor: Rc<Mutex<Editor>>, state: &Rc<Mutex<CropState>>) -> Result<()> {
+    let (x, y, width, height) = {
+        let mut editor = if let Some(editor) = Editor::try_lock(&editor) {
+            editor
+        } else {
+            return Ok(());
+        };
+        if editor.tool() != Tool::Crop {
+            return Ok(());
+        }
+        let mut state = lock_state(state)?;
+        stop_crop(&mut state, &mut editor)?;
+        let selection = &state.selection;
+        (
+            selection.x.round() as u32,
+            selection.y.round() as u32,
+            (selection.width.round() as u32).max(1),
+            (selection.height.round() as u32).max(1),
+        )
+    };
+    run_filter(editor, Rc::new(move |image_data| {
+        let (image_width, image_height) = image_data.size();
+        let width = width.min(image_width.saturating_sub(x));
+        let height = height.min(image_height.saturating_sub(y));
+        Ok(Box::new(Crop::new(image_data.data(), image_width, image_height, x, y, width, height)?))
+    }))
+}
+
+fn setup_crop_apply_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CropState>>) -> Result<()> {
+    let button_element = Button::new_from_id("crop_apply")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = crop_apply_closure(editor.clone(), &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn pointer_down(editor: &Editor, state: &mut CropState, event: &Event) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let (canvas_x, canvas_y) = canvas.event_position(event)?;
+    editor.draw_over_image(|image_data, renderer| {
+        let size = image_data.size();
+        let point = if let Some(point) = renderer.canvas_to_image_point(canvas_x, canvas_y, size) {
+            point
+        } else {
+            return Ok(());
+        };
+        // The image may have changed through undo or another filter since the tool was started.
+        state.image.set(image_data)?;
+        let bounds = image_rect(size);
+        if state.selection.x + state.selection.width > bounds.width ||
+                state.selection.y + state.selection.height > bounds.height {
+            state.selection = fit_aspect(&bounds, state.aspect);
+        }
+        let rect = renderer.image_to_canvas_rect(size, &state.selection);
+        let handle_size = HANDLE_SIZE * renderer.pixel_ratio();
+        let (handle, selection) = match hit_handle(&rect, canvas_x, canvas_y, handle_size) {
+            Some(handle) => (handle, state.selection.clone()),
+            None => {
+                let x = point.0.clamp(0.0, bounds.width);
+                let y = point.1.clamp(0.0, bounds.height);
+                (Handle::BottomRight, Rect::new(x, y, 0.0, 0.0))
+            },
+        };
+        state.drag = Some(Drag { handle, start: point, selection });
+        Ok(())
+    })
+}
+
+fn pointer_move(editor: &Editor, state: &mut CropState, event: &Event) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let (canvas_x, canvas_y) = canvas.event_position(event)?;
+    editor.draw_over_image(|image_data, renderer| {
+        let size = image_data.size();
+        let drag = if let Some(drag) = &state.drag {
+            drag
+        } else {
+            return Ok(());
+        };
+        if let Some((x, y)) = renderer.canvas_to_image_point(canvas_x, canvas_y, size) {
+            state.selection = drag_selection(
+                &drag.selection, drag.handle, (x - drag.start.0, y - drag.start.1), state.aspect, size);
+        }
+        draw_selection(state, image_data, renderer)
+    })?;
+    update_position_inputs(&state.selection)
+}
+
+fn pointer_up(editor: &Editor, state: &mut CropState) -> Result<()> {
+    if state.drag.take().is_none() {
+        return Ok(());
+    }
+    if state.selection.width < 1.0 || state.selection.height < 1.0 {
+        if let Some(image_data) = editor.get_image_data() {
+            state.selection = fit_aspect(&image_rect(image_data.size()), state.aspect);
+        }
+    }
+    refresh(state, editor)
+}
+
+fn crop_canvas_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CropState>>,
+                       event_name: &str, event: &Event) -> Result<()> {
+    let mut state = lock_state(state)?;
+    if event_name != "pointerdown" && state.drag.is_none() {
+        return Ok(());
+    }
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Crop {
+        return Ok(());
+    }
+    match event_name {
+        "pointerdown" => pointer_down(&editor, &mut state, event),
+        "pointermove" => pointer_move(&editor, &mut state, event),
+        _ => pointer_up(&editor, &mut state),
+    }
+}
+
+fn setup_crop_canvas_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CropState>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["pointerdown", "pointermove", "pointerup", "pointerleave"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = crop_canvas_closure(&editor, &state, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+/// Reads the numeric entry, keeping the aspect ratio by adjusting the dimension not typed in.
+fn position_input_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CropState>>, changed: &str) -> Result<()> {
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Crop {
+        return Ok(());
+    }
+    let (width, height) = if let Some(image_data) = editor.get_image_data() {
+        image_data.size()
+    } else {
+        return Ok(());
+    };
+    let mut state = lock_state(state)?;
+    let mut values = [0.0; 4];
+    for (value, id) in values.iter_mut().zip(POSITION_INPUT_IDS) {
+        *value = Input::new_from_id(id)?.value_as_number()?;
+    }
+    let x = values[0].clamp(0.0, width as f64 - 1.0);
+    let y = values[1].clamp(0.0, height as f64 - 1.0);
+    let (mut selection_width, mut selection_height) = (values[2].max(1.0), values[3].max(1.0));
+    if let Some(aspect) = state.aspect {
+        if changed == "crop_height" {
+            selection_width = selection_height * aspect;
+        } else {
+            selection_height = selection_width / aspect;
+        }
+    }
+    let selection = Rect::new(x, y, selection_width, selection_height);
+    let bounds = Rect::new(x, y, width as f64 - x, height as f64 - y);
+    state.selection = if selection.width > bounds.width || selection.height > bounds.height {
+        let fitted = fit_aspect(&bounds, state.aspect.or(Some(selection_width / selection_height)));
+        Rect::new(x, y, fitted.width, fitted.height)
+    } else {
+        selection
+    };
+    refresh(&state, &editor)
+}
+
+fn setup_position_input_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CropState>>) -> Result<()> {
+    for id in POSITION_INPUT_IDS {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |_event: Event| {
+            if let Err(err) = position_input_closure(&editor, &state, id) {
+                error!("{:#?}", err);
+            }
+        });
+        Input::new_from_id(id)?.set_onchange(&closure);
+        closure.forget();
+    }
+    Ok(())
+}
+
+fn crop_aspect_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<CropState>>) -> Result<()> {
+    let mut state = lock_state(state)?;
+    state.aspect = parse_aspect(&Select::new_from_id("crop_aspect")?.value())?;
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Crop {
+        return Ok(());
+    }
+    state.selection = fit_aspect(&state.selection, state.aspect);
+    refresh(&state, &editor)
+}
+
+fn setup_crop_aspect_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<CropState>>) -> Result<()> {
+    let select = Select::new_from_id("crop_aspect")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = crop_aspect_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    select.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+pub fn setup_crop_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let state = Rc::new(Mutex::new(CropState::new()));
+    setup_crop_button_event(editor.clone(), state.clone())?;
+    setup_crop_apply_event(editor.clone(), state.clone())?;
+    setup_crop_canvas_event(editor.clone(), state.clone())?;
+    setup_position_input_event(editor.clone(), state.clone())?;
+    setup_crop_aspect_event(editor, state)?;
+    Ok(())
+}
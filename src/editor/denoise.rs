@@ -0,0 +1,31 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::Input;
+use crate::filter::{Median, Bilateral, NonLocalMeans};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_denoise_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "median", Rc::new(|image_data| {
+        let radius = Input::new_from_id("median_radius")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Median::new(
+            image_data.data(), width, height, radius as u32)?))
+    }))?;
+    setup_filter_event(editor.clone(), "bilateral", Rc::new(|image_data| {
+        let radius = Input::new_from_id("bilateral_radius")?.value_as_number()?;
+        let sigma_color = Input::new_from_id("bilateral_sigma_color")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Bilateral::new(
+            image_data.data(), width, height, radius as u32, sigma_color)?))
+    }))?;
+    setup_filter_event(editor, "non_local_means", Rc::new(|image_data| {
+        let search_radius = Input::new_from_id("nlm_search_radius")?.value_as_number()?;
+        let strength = Input::new_from_id("nlm_strength")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(NonLocalMeans::new(
+            image_data.data(), width, height, search_radius as u32, strength)?))
+    }))
+}
@@ -0,0 +1,34 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::{Input, Select};
+use crate::filter::{GradientOperator, Gradient, LaplacianOfGaussian, Canny};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_edge_detection_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "gradient", Rc::new(|image_data| {
+        let operator: GradientOperator = Select::new_from_id("gradient_operator")?.value().parse()?;
+        let direction_coloring = Input::new_from_id("gradient_direction")?.checked();
+        let (width, height) = image_data.size();
+        Ok(Box::new(Gradient::new(
+            image_data.data(), width, height, operator, direction_coloring)))
+    }))?;
+    setup_filter_event(editor.clone(), "laplacian_of_gaussian", Rc::new(|image_data| {
+        let radius = Input::new_from_id("log_radius")?.value_as_number()?;
+        let threshold = Input::new_from_id("log_threshold")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(LaplacianOfGaussian::new(
+            image_data.data(), width, height, radius as u32, threshold)?))
+    }))?;
+    setup_filter_event(editor, "canny", Rc::new(|image_data| {
+        let radius = Input::new_from_id("canny_radius")?.value_as_number()?;
+        let low = Input::new_from_id("canny_low")?.value_as_number()?;
+        let high = Input::new_from_id("canny_high")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Canny::new(
+            image_data.data(), width, height, radius as u32, low, high)?))
+    }))?;
+    Ok(())
+}
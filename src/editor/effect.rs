@@ -0,0 +1,44 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::Input;
+use crate::filter::{PointOperation, PointFilter, parse_hex_color};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+fn setup_point_filter_event(
+    editor: Rc<Mutex<Editor>>,
+    button_id: &str,
+    operation: impl Fn() -> Result<PointOperation> + 'static,
+) -> Result<()> {
+    setup_filter_event(editor, button_id, Rc::new(move |image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(PointFilter::new(operation()?, width, height)?))
+    }))
+}
+
+pub fn setup_effect_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_point_filter_event(editor.clone(), "invert", || {
+        let preserve_alpha = Input::new_from_id("invert_preserve_alpha")?.checked();
+        Ok(PointOperation::Invert { preserve_alpha })
+    })?;
+    setup_point_filter_event(editor.clone(), "sepia", || {
+        let strength = Input::new_from_id("sepia_strength")?.value_as_number()?;
+        Ok(PointOperation::Sepia { strength: strength / 100.0 })
+    })?;
+    setup_point_filter_event(editor.clone(), "posterize", || {
+        let levels = Input::new_from_id("posterize_levels")?.value_as_number()?;
+        Ok(PointOperation::Posterize { levels: levels as u32 })
+    })?;
+    setup_point_filter_event(editor.clone(), "solarize", || {
+        let threshold = Input::new_from_id("solarize_threshold")?.value_as_number()?;
+        Ok(PointOperation::Solarize { threshold: threshold.clamp(0.0, 255.0) as u8 })
+    })?;
+    setup_point_filter_event(editor, "duotone", || {
+        let dark = parse_hex_color(&Input::new_from_id("duotone_dark")?.value())?;
+        let light = parse_hex_color(&Input::new_from_id("duotone_light")?.value())?;
+        Ok(PointOperation::Duotone { dark, light })
+    })?;
+    Ok(())
+}
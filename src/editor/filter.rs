@@ -0,0 +1,163 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, ImageDataWrapper};
+use crate::filter::Filter;
+use super::Editor;
+
+pub type FilterFactory = Rc<dyn Fn(&ImageDataWrapper) -> Result<Box<dyn Filter>>>;
+
+struct Task {
+    filter: Box<dyn Filter>,
+    data: Vec<u8>,
+}
+
+impl Task {
+    /// Reallocates `data` when the filter changed its output size while measuring the image.
+    fn fit_data(&mut self) {
+        let (width, height) = self.filter.output_size();
+        let len = width as usize * height as usize * 4;
+        if self.data.len() != len {
+            self.data = vec![0; len];
+        }
+    }
+}
+
+fn final_step_inner(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
+    if !Editor::try_run_id(&editor, id) {
+        final_step(editor, id)?;
+        return Ok(());
+    }
+    let mut editor = Editor::lock(&editor)?;
+    let result = editor.draw_image_data();
+    editor.set_disabled(false);
+    editor.to_idle();
+    result
+}
+
+fn final_step(editor: Rc<Mutex<Editor>>, id: u16) -> Result<()> {
+    browser::set_callback_once(move || {
+        browser::spawn_local(async move {
+            if let Err(err) = final_step_inner(editor, id) {
+                error!("{:#?}", err);
+            }
+        });
+    })
+}
+
+fn filter_step_callback_inner(editor: Rc<Mutex<Editor>>, task: Task, id: u16) -> Result<()> {
+    if !Editor::try_run_id(&editor, id) {
+        filter_step_callback(editor, task, id)?;
+        return Ok(());
+    }
+    filter_step_thread(editor, task, id)
+}
+
+fn filter_step_callback(editor: Rc<Mutex<Editor>>, task: Task, id: u16) -> Result<()> {
+    browser::set_callback_once(move || {
+        browser::spawn_local(async move {
+            if let Err(err) = filter_step_callback_inner(editor, task, id) {
+                error!("{:#?}", err);
+            }
+        });
+    })
+}
+
+fn filter_step_thread(editor: Rc<Mutex<Editor>>, mut task: Task, id: u16) -> Result<()> {
+    if !task.filter.step(&mut task.data) {
+        task.fit_data();
+        return filter_step_callback(editor, task, id);
+    }
+    {
+        let (width, height) = task.filter.output_size();
+        let image_data = ImageDataWrapper::new_from_data(task.data, width, height)?;
+        let mut editor = Editor::lock(&editor)?;
+        editor.reset_tool()?;
+        editor.push_image_data(image_data);
+    }
+    final_step(editor, id)
+}
+
+fn set_disabled_false(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let mut editor = Editor::lock(&editor)?;
+    editor.set_disabled(false);
+    editor.to_idle();
+    Ok(())
+}
+
+fn make_task(editor: &Rc<Mutex<Editor>>, factory: &FilterFactory) -> Result<Option<Task>> {
+    let editor = Editor::lock(editor)?;
+    let image_data = if let Some(image_data) = editor.get_image_data() {
+        image_data
+    } else {
+        log!("No image data");
+        return Ok(None);
+    };
+    let filter = factory(image_data)?;
+    let (width, height) = filter.output_size();
+    let data = if (width, height) == image_data.size() {
+        image_data.data().to_vec()
+    } else {
+        vec![0; width as usize * height as usize * 4]
+    };
+    Ok(Some(Task { filter, data }))
+}
+
+fn first_step(editor: Rc<Mutex<Editor>>, factory: FilterFactory, id: u16) -> Result<()> {
+    if !Editor::try_run_id(&editor, id) {
+        set_callback_first_step(editor, factory, id)?;
+        return Ok(());
+    }
+    match make_task(&editor, &factory) {
+        Ok(Some(task)) => filter_step_thread(editor, task, id),
+        Ok(None) => set_disabled_false(editor),
+        Err(err) => {
+            set_disabled_false(editor)?;
+            Err(err)
+        },
+    }
+}
+
+fn set_callback_first_step(editor: Rc<Mutex<Editor>>, factory: FilterFactory, id: u16) -> Result<()> {
+    browser::set_callback_once(move || {
+        browser::spawn_local(async move {
+            if let Err(err) = first_step(editor, factory, id) {
+                error!("{:#?}", err);
+            }
+        });
+    })
+}
+
+/// Runs the filter made by `factory` on the current image and pushes the result to the history.
+pub fn run_filter(editor: Rc<Mutex<Editor>>, factory: FilterFactory) -> Result<()> {
+    let id = if let Some(id) = Editor::try_run(&editor) {
+        id
+    } else {
+        return Ok(());
+    };
+    if let Some(editor) = Editor::try_lock(&editor) {
+        editor.set_disabled(true);
+    } else {
+        return Ok(())
+    }
+    set_callback_first_step(editor, factory, id)
+}
+
+pub fn setup_filter_event(editor: Rc<Mutex<Editor>>, button_id: &str, factory: FilterFactory) -> Result<()> {
+    let button_element = Button::new_from_id(button_id)?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = run_filter(editor_clone, factory.clone()) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
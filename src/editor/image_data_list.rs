@@ -21,6 +21,19 @@ impl ImageDataList {
         self.image_data_list.get(self.current_index)
     }
 
+    /// Entry `index` of the history, counted from the oldest one kept.
+    pub fn get(&self, index: usize) -> Option<&ImageDataWrapper> {
+        self.image_data_list.get(index)
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current_index
+    }
+
+    pub fn len(&self) -> usize {
+        self.image_data_list.len()
+    }
+
     pub fn get_image_data_inner_mut(&mut self) -> Option<&mut [u8]> {
         self.image_data_list.get_mut(self.current_index)
             .map(|image_data| image_data.data_mut())
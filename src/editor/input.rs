@@ -4,13 +4,17 @@ use std::sync::Mutex;
 use anyhow::Result;
 use wasm_bindgen::JsCast;
 use web_sys::{
-    Event, FileReader,
+    Event, File, FileReader,
 };
 use crate::browser;
 use crate::engine::{
-    Image, Input,
+    Canvas, Image, Input,
 };
-use super::Editor;
+#[cfg(feature = "native-decode")]
+use crate::engine::ImageDataWrapper;
+#[cfg(feature = "native-decode")]
+use crate::decode::decode_image;
+use super::{Editor, viewport};
 
 async fn internal_draw_image_fit_canvas_from_source(editor: Rc<Mutex<Editor>>, source: String, id: u16) -> Result<()> {
     if !Editor::try_run_id(&editor, id) {
@@ -20,10 +24,13 @@ async fn internal_draw_image_fit_canvas_from_source(editor: Rc<Mutex<Editor>>, s
     let image = Image::load_image(source.as_str())
          .await?;
     let mut editor = Editor::lock(&editor)?;
+    editor.reset_tool()?;
     editor.set_image(image);
     editor.update_canvas_size()?;
+    editor.set_zoom(None);
     editor.draw_image_fit_canvas()?;
     editor.setup_image_data()?;
+    viewport::show_zoom(&editor)?;
     editor.set_disabled(true);
     editor.set_disabled(false);
     editor.to_idle();
@@ -45,6 +52,122 @@ fn setup_input_event_closure_reader_closure(editor: Rc<Mutex<Editor>>, reader: &
     Ok(())
 }
 
+fn read_image_file_as_data_url(editor: Rc<Mutex<Editor>>, file: &File, id: u16) -> Result<()> {
+    let reader = browser::file_reader()?;
+
+    let reader_ref = Rc::new(RefCell::new(reader));
+    let reader_clone = reader_ref.clone();
+
+    let onload_closure = browser::create_event_closure(move |_event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = setup_input_event_closure_reader_closure(editor_clone, &reader_clone.borrow(), id) {
+            error!("{:#?}", err);
+        }
+    });
+
+    reader_ref.borrow_mut().set_onload(Some(onload_closure.as_ref().unchecked_ref()));
+    onload_closure.forget();
+
+    browser::file_reader_read_as_data_url(&reader_ref.borrow(), file)?;
+    Ok(())
+}
+
+#[cfg(feature = "native-decode")]
+async fn internal_load_decoded_image(editor: Rc<Mutex<Editor>>, image_data: ImageDataWrapper, id: u16) -> Result<()> {
+    if !Editor::try_run_id(&editor, id) {
+        load_decoded_image(editor, image_data, id);
+        return Ok(());
+    }
+    let mut editor = Editor::lock(&editor)?;
+    editor.reset_tool()?;
+    editor.set_decoded_image(image_data);
+    editor.update_canvas_size()?;
+    editor.set_zoom(None);
+    editor.draw_image_data()?;
+    viewport::show_zoom(&editor)?;
+    editor.set_disabled(true);
+    editor.set_disabled(false);
+    editor.to_idle();
+    Ok(())
+}
+
+#[cfg(feature = "native-decode")]
+fn load_decoded_image(editor: Rc<Mutex<Editor>>, image_data: ImageDataWrapper, id: u16) {
+    browser::spawn_local(async move {
+        if let Err(err) = internal_load_decoded_image(editor, image_data, id).await {
+            error!("{:#?}", err);
+        }
+    });
+}
+
+/// Decodes the bytes in Rust, and leaves formats it does not know to the browser.
+#[cfg(feature = "native-decode")]
+fn decode_reader_closure(editor: Rc<Mutex<Editor>>, reader: &FileReader, file: &File, id: u16) -> Result<()> {
+    let bytes = browser::file_reader_result_bytes(reader)?;
+    match decode_image(&bytes) {
+        Ok((data, width, height)) => {
+            let image_data = ImageDataWrapper::new_from_data(data, width, height)?;
+            load_decoded_image(editor, image_data, id);
+            Ok(())
+        },
+        Err(err) => {
+            log!("{:#}, decoding in the browser", err);
+            read_image_file_as_data_url(editor, file, id)
+        },
+    }
+}
+
+#[cfg(feature = "native-decode")]
+fn read_image_file_as_bytes(editor: Rc<Mutex<Editor>>, file: &File, id: u16) -> Result<()> {
+    let reader = browser::file_reader()?;
+
+    let reader_ref = Rc::new(RefCell::new(reader));
+    let reader_clone = reader_ref.clone();
+    let file_clone = file.clone();
+
+    let onload_closure = browser::create_event_closure(move |_event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = decode_reader_closure(editor_clone, &reader_clone.borrow(), &file_clone, id) {
+            error!("{:#?}", err);
+        }
+    });
+
+    reader_ref.borrow_mut().set_onload(Some(onload_closure.as_ref().unchecked_ref()));
+    onload_closure.forget();
+
+    browser::file_reader_read_as_array_buffer(&reader_ref.borrow(), file)?;
+    Ok(())
+}
+
+/// Reads the file with the decoder chosen by the exact decoding checkbox.
+#[cfg(feature = "native-decode")]
+fn read_image_file(editor: Rc<Mutex<Editor>>, file: &File, id: u16) -> Result<()> {
+    if Input::new_from_id("native_decode")?.checked() {
+        read_image_file_as_bytes(editor, file, id)
+    } else {
+        read_image_file_as_data_url(editor, file, id)
+    }
+}
+
+#[cfg(not(feature = "native-decode"))]
+fn read_image_file(editor: Rc<Mutex<Editor>>, file: &File, id: u16) -> Result<()> {
+    read_image_file_as_data_url(editor, file, id)
+}
+
+/// Loads a dropped or pasted file the same way as one chosen with the file input.
+fn load_image_file(editor: Rc<Mutex<Editor>>, file: &File) -> Result<()> {
+    let id = if let Some(id) = Editor::try_run(&editor) {
+        id
+    } else {
+        return Ok(());
+    };
+    if let Err(err) = read_image_file(editor.clone(), file, id) {
+        Editor::lock(&editor)?.to_idle();
+        return Err(err);
+    }
+    Ok(())
+}
+
 fn setup_input_event_closure(editor: Rc<Mutex<Editor>>, event: Event) -> Result<()> {
     let id = if let Some(id) = Editor::try_run(&editor) {
         id
@@ -53,29 +176,73 @@ fn setup_input_event_closure(editor: Rc<Mutex<Editor>>, event: Event) -> Result<
     };
     let input = Input::new_from_event(&event)?;
     if let Some(file) = input.get_first_image_file()? {
-        let reader = browser::file_reader()?;
+        read_image_file(editor, &file, id)?;
+    } else {
+        Editor::lock(&editor)?.to_idle();
+    }
+    Ok(())
+}
 
-        let reader_ref = Rc::new(RefCell::new(reader));
-        let reader_clone = reader_ref.clone();
+fn drag_closure(editor: &Rc<Mutex<Editor>>, event_name: &str, event: &Event) -> Result<()> {
+    // Without preventing the default the browser opens the dropped file itself.
+    event.prevent_default();
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    match event_name {
+        "dragenter" | "dragover" => {
+            browser::drag_event_set_drop_effect_copy(event)?;
+            canvas.set_class("drop_target", true)
+        },
+        "drop" => {
+            canvas.set_class("drop_target", false)?;
+            match browser::drag_event_image_file(event)? {
+                Some(file) => load_image_file(editor.clone(), &file),
+                None => Ok(()),
+            }
+        },
+        _ => canvas.set_class("drop_target", false),
+    }
+}
 
-        let onload_closure = browser::create_event_closure(move |_event: Event| {
-            let editor_clone = editor.clone();
-            if let Err(err) = setup_input_event_closure_reader_closure(editor_clone, &reader_clone.borrow(), id) {
+fn setup_drop_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["dragenter", "dragover", "dragleave", "drop"] {
+        let editor = editor.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = drag_closure(&editor, event_name, &event) {
                 error!("{:#?}", err);
             }
         });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
 
-        reader_ref.borrow_mut().set_onload(Some(onload_closure.as_ref().unchecked_ref()));
-        onload_closure.forget();
+    Ok(())
+}
 
-        browser::file_reader_read_as_data_url(&reader_ref.borrow(), &file)?;
-    } else {
-        Editor::lock(&editor)?.to_idle();
+fn paste_closure(editor: &Rc<Mutex<Editor>>, event: &Event) -> Result<()> {
+    // Pasted text is left to the focused input.
+    if let Some(file) = browser::clipboard_event_image_file(event)? {
+        event.prevent_default();
+        load_image_file(editor.clone(), &file)?;
     }
     Ok(())
 }
 
-pub fn setup_input_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+fn setup_paste_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let closure = browser::create_event_closure(move |event: Event| {
+        if let Err(err) = paste_closure(&editor, &event) {
+            error!("{:#?}", err);
+        }
+    });
+
+    browser::add_event_listener_with_callback_window("paste", &closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn setup_file_input_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
     let input_element = Input::new_from_id("file_input")?;
 
     let closure = browser::create_event_closure(move |event: Event| {
@@ -90,3 +257,10 @@ pub fn setup_input_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
 
     Ok(())
 }
+
+pub fn setup_input_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_file_input_event(editor.clone())?;
+    setup_drop_event(editor.clone())?;
+    setup_paste_event(editor)?;
+    Ok(())
+}
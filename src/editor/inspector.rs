@@ -0,0 +1,149 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Input, Select, Text};
+use crate::filter::{format_hex_color, rgb_to_hsl, luminance};
+use super::{Editor, Tool};
+
+/// Position in the image and RGBA color of a pixel.
+type Pixel = ((u32, u32), [u8; 4]);
+
+/// The pixel under the pointer of `event`.
+fn pixel_at(editor: &Editor, event: &Event) -> Result<Option<Pixel>> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let (canvas_x, canvas_y) = canvas.event_position(event)?;
+    let (x, y) = match editor.canvas_to_image_position(canvas_x, canvas_y) {
+        Some(position) => position,
+        None => return Ok(None),
+    };
+    let image_data = match editor.get_image_data() {
+        Some(image_data) => image_data,
+        None => return Ok(None),
+    };
+    let index = ((y * image_data.size().0 + x) * 4) as usize;
+    let mut color = [0; 4];
+    color.copy_from_slice(&image_data.data()[index..index + 4]);
+    Ok(Some(((x, y), color)))
+}
+
+fn describe_pixel((x, y): (u32, u32), [r, g, b, a]: [u8; 4]) -> String {
+    let (hue, saturation, lightness) = rgb_to_hsl(r, g, b);
+    format!(
+        "({}, {}) RGBA {} {} {} {} {} HSL {:.0}° {:.0}% {:.0}% Y {:.1}",
+        x, y, r, g, b, a, format_hex_color([r, g, b]),
+        hue, saturation * 100.0, lightness * 100.0, luminance(r, g, b),
+    )
+}
+
+fn hover_closure(editor: &Rc<Mutex<Editor>>, event_name: &str, event: &Event) -> Result<()> {
+    // Hovering must not get in the way of a running filter, so a busy editor is skipped quietly.
+    let editor = if let Ok(editor) = editor.try_lock() {
+        editor
+    } else {
+        return Ok(());
+    };
+    let text = match event_name {
+        "pointermove" => pixel_at(&editor, event)?
+            .map(|(position, color)| describe_pixel(position, color))
+            .unwrap_or_default(),
+        _ => String::new(),
+    };
+    Text::new_from_id("pixel_info")?.set_text(&text);
+    Ok(())
+}
+
+fn setup_hover_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["pointermove", "pointerleave"] {
+        let editor = editor.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = hover_closure(&editor, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+fn eyedropper_button_closure(editor: &Rc<Mutex<Editor>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() == Tool::Eyedropper {
+        editor.set_tool(Tool::None)
+    } else {
+        editor.set_tool(Tool::Eyedropper)
+    }
+}
+
+fn setup_eyedropper_button_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let button_element = Button::new_from_id("eyedropper")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = eyedropper_button_closure(&editor) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+/// Stores the color under the cursor in the picked color input, and copies it to
+/// the color input chosen as target.
+fn eyedropper_canvas_closure(editor: &Rc<Mutex<Editor>>, event: &Event) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Eyedropper {
+        return Ok(());
+    }
+    let (position, color) = match pixel_at(&editor, event)? {
+        Some(pixel) => pixel,
+        None => return Ok(()),
+    };
+    editor.set_tool(Tool::None)?;
+    let hex = format_hex_color([color[0], color[1], color[2]]);
+    Input::new_from_id("picked_color")?.set_value(&hex);
+    let target = Select::new_from_id("pick_target")?.value();
+    if target != "none" {
+        Input::new_from_id(&target)?.set_value(&hex);
+    }
+    Text::new_from_id("picked_report")?.set_text(&describe_pixel(position, color));
+    Ok(())
+}
+
+fn setup_eyedropper_canvas_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    let closure = browser::create_event_closure(move |event: Event| {
+        if let Err(err) = eyedropper_canvas_closure(&editor, &event) {
+            error!("{:#?}", err);
+        }
+    });
+
+    canvas.add_event_listener_with_callback("click", &closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+pub fn setup_inspector_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_hover_event(editor.clone())?;
+    setup_eyedropper_button_event(editor.clone())?;
+    setup_eyedropper_canvas_event(editor)?;
+    Ok(())
+}
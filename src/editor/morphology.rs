@@ -0,0 +1,25 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::{Input, Select};
+use crate::filter::{StructuringElement, MorphologyOperation, Morphology, RemoveSpecks};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_morphology_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "morphology", Rc::new(|image_data| {
+        let operation: MorphologyOperation = Select::new_from_id("morphology_operation")?.value().parse()?;
+        let element: StructuringElement = Select::new_from_id("morphology_element")?.value().parse()?;
+        let radius = Input::new_from_id("morphology_radius")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Morphology::new(
+            operation, element, image_data.data(), width, height, radius as u32)?))
+    }))?;
+    setup_filter_event(editor, "remove_specks", Rc::new(|image_data| {
+        let min_size = Input::new_from_id("speck_size")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(RemoveSpecks::new(
+            image_data.data(), width, height, min_size as u32)))
+    }))
+}
@@ -0,0 +1,24 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::filter::{Orientation, Reorient};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_orientation_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let buttons = [
+        ("rotate_90", Orientation::Rotate90),
+        ("rotate_180", Orientation::Rotate180),
+        ("rotate_270", Orientation::Rotate270),
+        ("flip_horizontal", Orientation::FlipHorizontal),
+        ("flip_vertical", Orientation::FlipVertical),
+    ];
+    for (button_id, orientation) in buttons {
+        setup_filter_event(editor.clone(), button_id, Rc::new(move |image_data| {
+            let (width, height) = image_data.size();
+            Ok(Box::new(Reorient::new(orientation, image_data.data(), width, height)))
+        }))?;
+    }
+    Ok(())
+}
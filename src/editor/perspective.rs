@@ -0,0 +1,263 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Rect, Select};
+use crate::filter::{Interpolation, Perspective, order_corners, estimate_rectangle_size};
+use super::{BaseCanvas, Editor, Tool, ToolOverlay, lock_state};
+use super::crop::parse_aspect;
+use super::filter::run_filter;
+
+const HANDLE_SIZE: f64 = 8.0;
+const OUTLINE_COLOR: &str = "#ff9800";
+
+#[derive(Debug, Default)]
+struct PerspectiveState {
+    /// Corner points in image pixels, in the order they were placed.
+    points: Vec<(f64, f64)>,
+    /// Index of the point being dragged.
+    drag: Option<usize>,
+    image: BaseCanvas,
+}
+
+impl PerspectiveState {
+    fn corners(&self) -> Option<[(f64, f64); 4]> {
+        let points: [(f64, f64); 4] = self.points.as_slice().try_into().ok()?;
+        Some(order_corners(&points))
+    }
+}
+
+impl ToolOverlay for PerspectiveState {
+    fn redraw(&mut self, editor: &Editor) -> Result<()> {
+        draw_points(editor, self)
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        *self = Self::default();
+        Ok(())
+    }
+}
+
+fn draw_points(editor: &Editor, state: &PerspectiveState) -> Result<()> {
+    editor.draw_over_image(|image_data, renderer| {
+        let size = image_data.size();
+        state.image.draw(renderer)?;
+        let to_canvas = |(x, y): (f64, f64)| {
+            let rect = renderer.image_to_canvas_rect(size, &Rect::new(x, y, 0.0, 0.0));
+            (rect.x, rect.y)
+        };
+        let points: Vec<(f64, f64)> = match state.corners() {
+            Some(corners) => corners.iter().copied().map(to_canvas).collect(),
+            None => state.points.iter().copied().map(to_canvas).collect(),
+        };
+        for pair in points.windows(2) {
+            renderer.stroke_line(pair[0], pair[1], OUTLINE_COLOR);
+        }
+        if let (4, Some(first), Some(last)) = (points.len(), points.first(), points.last()) {
+            renderer.stroke_line(*last, *first, OUTLINE_COLOR);
+        }
+        let handle_size = HANDLE_SIZE * renderer.pixel_ratio();
+        for (x, y) in points {
+            let handle = Rect::new(x - handle_size / 2.0, y - handle_size / 2.0, handle_size, handle_size);
+            renderer.fill_rect(&handle, OUTLINE_COLOR);
+        }
+        Ok(())
+    })
+}
+
+/// Leaves the tool and draws the image without the points.
+fn stop_perspective(editor: &mut Editor, state: &mut PerspectiveState) -> Result<()> {
+    state.points.clear();
+    state.drag = None;
+    editor.set_tool(Tool::None)?;
+    draw_points(editor, state)?;
+    state.image.clear();
+    Ok(())
+}
+
+fn perspective_button_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<PerspectiveState>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let overlay = state.clone();
+    let mut state = lock_state(state)?;
+    if editor.tool() == Tool::Perspective {
+        return stop_perspective(&mut editor, &mut state);
+    }
+    let image_data = if let Some(image_data) = editor.get_image_data() {
+        image_data
+    } else {
+        log!("No image data");
+        return Ok(());
+    };
+    state.points.clear();
+    state.image.set(image_data)?;
+    editor.set_tool(Tool::Perspective)?;
+    editor.set_tool_overlay(overlay);
+    Ok(())
+}
+
+fn setup_perspective_button_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<PerspectiveState>>) -> Result<()> {
+    let button_element = Button::new_from_id("perspective")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = perspective_button_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn pointer_down(editor: &Editor, state: &mut PerspectiveState, position: (f64, f64)) -> Result<()> {
+    let mut grabbed = None;
+    let mut point = None;
+    editor.draw_over_image(|image_data, renderer| {
+        let size = image_data.size();
+        let handle_size = HANDLE_SIZE * renderer.pixel_ratio();
+        grabbed = state.points.iter().position(|&(x, y)| {
+            let rect = renderer.image_to_canvas_rect(size, &Rect::new(x, y, 0.0, 0.0));
+            (rect.x - position.0).abs() <= handle_size && (rect.y - position.1).abs() <= handle_size
+        });
+        point = renderer.canvas_to_image_point(position.0, position.1, size)
+            .map(|(x, y)| (x.clamp(0.0, size.0 as f64), y.clamp(0.0, size.1 as f64)));
+        Ok(())
+    })?;
+    state.drag = match (grabbed, point) {
+        (Some(index), _) => Some(index),
+        (None, Some(point)) if state.points.len() < 4 => {
+            state.points.push(point);
+            Some(state.points.len() - 1)
+        },
+        _ => None,
+    };
+    draw_points(editor, state)
+}
+
+fn pointer_move(editor: &Editor, state: &mut PerspectiveState, position: (f64, f64)) -> Result<()> {
+    let index = if let Some(index) = state.drag {
+        index
+    } else {
+        return Ok(());
+    };
+    let mut point = None;
+    editor.draw_over_image(|image_data, renderer| {
+        let size = image_data.size();
+        point = renderer.canvas_to_image_point(position.0, position.1, size)
+            .map(|(x, y)| (x.clamp(0.0, size.0 as f64), y.clamp(0.0, size.1 as f64)));
+        Ok(())
+    })?;
+    if let Some(point) = point {
+        state.points[index] = point;
+    }
+    draw_points(editor, state)
+}
+
+fn perspective_canvas_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<PerspectiveState>>,
+                              event_name: &str, event: &Event) -> Result<()> {
+    let mut state = lock_state(state)?;
+    if event_name != "pointerdown" && state.drag.is_none() {
+        return Ok(());
+    }
+    let editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    if editor.tool() != Tool::Perspective {
+        return Ok(());
+    }
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let position = canvas.event_position(event)?;
+    match event_name {
+        "pointerdown" => pointer_down(&editor, &mut state, position),
+        "pointermove" => pointer_move(&editor, &mut state, position),
+        _ => {
+            state.drag = None;
+            Ok(())
+        },
+    }
+}
+
+fn setup_perspective_canvas_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<PerspectiveState>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["pointerdown", "pointermove", "pointerup", "pointerleave"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = perspective_canvas_closure(&editor, &state, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+fn perspective_apply_closure(editor: Rc<Mutex<Editor>>, state: &Rc<Mutex<PerspectiveState>>) -> Result<()> {
+    let corners = {
+        let mut editor = if let Some(editor) = Editor::try_lock(&editor) {
+            editor
+        } else {
+            return Ok(());
+        };
+        if editor.tool() != Tool::Perspective {
+            return Ok(());
+        }
+        let mut state = lock_state(state)?;
+        let corners = if let Some(corners) = state.corners() {
+            corners
+        } else {
+            log!("Place four corner points first");
+            return Ok(());
+        };
+        stop_perspective(&mut editor, &mut state)?;
+        corners
+    };
+    run_filter(editor, Rc::new(move |image_data| {
+        let aspect = parse_aspect(&Select::new_from_id("perspective_aspect")?.value())?;
+        let interpolation: Interpolation = Select::new_from_id("perspective_interpolation")?.value().parse()?;
+        let (estimated_width, estimated_height) = estimate_rectangle_size(&corners);
+        let (output_width, output_height) = match aspect {
+            Some(aspect) => (estimated_width, estimated_width / aspect),
+            None => (estimated_width, estimated_height),
+        };
+        let (width, height) = image_data.size();
+        Ok(Box::new(Perspective::new(
+            image_data.data(), width, height, &corners,
+            (output_width.round() as u32).max(1), (output_height.round() as u32).max(1), interpolation)?))
+    }))
+}
+
+fn setup_perspective_apply_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<PerspectiveState>>) -> Result<()> {
+    let button_element = Button::new_from_id("perspective_apply")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = perspective_apply_closure(editor.clone(), &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+pub fn setup_perspective_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let state = Rc::new(Mutex::new(PerspectiveState::default()));
+    setup_perspective_button_event(editor.clone(), state.clone())?;
+    setup_perspective_canvas_event(editor.clone(), state.clone())?;
+    setup_perspective_apply_event(editor, state)?;
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, ImageDataWrapper, Rect};
+use crate::filter::copy_region;
+use super::Editor;
+use super::filter::FilterFactory;
+
+const PREVIEW_MAX_SIZE: u32 = 1024;
+const PREVIEW_MARGIN: u32 = 64;
+const PREVIEW_STROKE_COLOR: &str = "#ff9800";
+
+/// Part of the image shown in the preview and the area around it the filter may read.
+fn preview_regions(width: u32, height: u32) -> (Rect, Rect) {
+    let region_width = (width / 2).clamp(1, PREVIEW_MAX_SIZE);
+    let region_height = (height / 2).clamp(1, PREVIEW_MAX_SIZE);
+    let x = (width - region_width) / 2;
+    let y = (height - region_height) / 2;
+    let padded_x = x.saturating_sub(PREVIEW_MARGIN);
+    let padded_y = y.saturating_sub(PREVIEW_MARGIN);
+    let padded_width = (x + region_width + PREVIEW_MARGIN).min(width) - padded_x;
+    let padded_height = (y + region_height + PREVIEW_MARGIN).min(height) - padded_y;
+    (
+        Rect::new(x as f64, y as f64, region_width as f64, region_height as f64),
+        Rect::new(padded_x as f64, padded_y as f64, padded_width as f64, padded_height as f64),
+    )
+}
+
+fn run_preview(image_data: &ImageDataWrapper, factory: &FilterFactory) -> Result<(Rect, ImageDataWrapper)> {
+    let (width, height) = image_data.size();
+    let (region, padded) = preview_regions(width, height);
+    let (padded_width, padded_height) = (padded.width as u32, padded.height as u32);
+    let padded_data = ImageDataWrapper::new_from_data(
+        copy_region(image_data.data(), width, padded.x as u32, padded.y as u32, padded_width, padded_height),
+        padded_width,
+        padded_height,
+    )?;
+    let mut filter = factory(&padded_data)?;
+    let mut data = padded_data.data().to_vec();
+    while !filter.step(&mut data) {}
+    let preview = copy_region(
+        &data,
+        padded_width,
+        (region.x - padded.x) as u32,
+        (region.y - padded.y) as u32,
+        region.width as u32,
+        region.height as u32,
+    );
+    let preview = ImageDataWrapper::new_from_data(preview, region.width as u32, region.height as u32)?;
+    Ok((region, preview))
+}
+
+fn setup_preview_event_closure(editor: Rc<Mutex<Editor>>, factory: FilterFactory) -> Result<()> {
+    if Editor::try_run(&editor).is_none() {
+        return Ok(());
+    }
+    let mut editor = Editor::lock(&editor)?;
+    let result = editor.draw_over_image(|image_data, renderer| {
+        let (region, preview) = run_preview(image_data, &factory)?;
+        let canvas = Canvas::new_from_image_data(&preview)?;
+        let rect = renderer.image_to_canvas_rect(image_data.size(), &region);
+        renderer.draw_canvas(&canvas, &rect)?;
+        renderer.stroke_rect(&rect, PREVIEW_STROKE_COLOR);
+        Ok(())
+    });
+    editor.to_idle();
+    result
+}
+
+/// Draws the result of the filter on the center of the image without touching the history.
+pub fn setup_preview_event(editor: Rc<Mutex<Editor>>, button_id: &str, factory: FilterFactory) -> Result<()> {
+    let button_element = Button::new_from_id(button_id)?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = setup_preview_event_closure(editor_clone, factory.clone()) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
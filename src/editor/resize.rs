@@ -0,0 +1,96 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::{Result, anyhow};
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Input, Select};
+use crate::filter::{Resize, ResizeFilter, check_image_size};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+/// Largest size with the aspect ratio of `(width, height)` fitting in the box.
+fn fit_in_box((width, height): (u32, u32), (box_width, box_height): (f64, f64)) -> (f64, f64) {
+    let scale = (box_width / width as f64).min(box_height / height as f64);
+    (width as f64 * scale, height as f64 * scale)
+}
+
+/// Output size chosen by the resize mode and the inputs of that mode.
+fn target_size(size: (u32, u32)) -> Result<(u32, u32)> {
+    let (width, height) = (size.0 as f64, size.1 as f64);
+    let keep_aspect = Input::new_from_id("resize_keep_aspect")?.checked();
+    let (target_width, target_height) = match Select::new_from_id("resize_mode")?.value().as_str() {
+        "pixels" => {
+            let target = (
+                Input::new_from_id("resize_width")?.value_as_number()?,
+                Input::new_from_id("resize_height")?.value_as_number()?,
+            );
+            if keep_aspect { fit_in_box(size, target) } else { target }
+        },
+        "percent" => {
+            let scale = Input::new_from_id("resize_percent")?.value_as_number()? / 100.0;
+            (width * scale, height * scale)
+        },
+        "long_edge" => {
+            let long_edge = Input::new_from_id("resize_long_edge")?.value_as_number()?;
+            fit_in_box(size, (long_edge, long_edge))
+        },
+        mode => return Err(anyhow!("Unknown resize mode {:#?}", mode)),
+    };
+    if !(target_width >= 0.5 && target_height >= 0.5) {
+        return Err(anyhow!("Resize target {}x{} is too small", target_width, target_height));
+    }
+    let target = (target_width.round() as u32, target_height.round() as u32);
+    check_image_size(target.0, target.1)?;
+    Ok(target)
+}
+
+/// Keeps the other dimension in step with the image aspect ratio while the lock is on.
+fn resize_dimension_closure(editor: &Rc<Mutex<Editor>>, changed: &str) -> Result<()> {
+    if !Input::new_from_id("resize_keep_aspect")?.checked() {
+        return Ok(());
+    }
+    let (width, height) = if let Some(editor) = Editor::try_lock(editor) {
+        if let Some(image_data) = editor.get_image_data() {
+            image_data.size()
+        } else {
+            return Ok(());
+        }
+    } else {
+        return Ok(());
+    };
+    let (source, target, ratio) = if changed == "resize_width" {
+        ("resize_width", "resize_height", height as f64 / width as f64)
+    } else {
+        ("resize_height", "resize_width", width as f64 / height as f64)
+    };
+    let value = Input::new_from_id(source)?.value_as_number()?;
+    Input::new_from_id(target)?.set_value(&(value * ratio).round().to_string());
+    Ok(())
+}
+
+fn setup_resize_dimension_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    for id in ["resize_width", "resize_height"] {
+        let editor = editor.clone();
+        let closure = browser::create_event_closure(move |_event: Event| {
+            if let Err(err) = resize_dimension_closure(&editor, id) {
+                error!("{:#?}", err);
+            }
+        });
+        Input::new_from_id(id)?.set_onchange(&closure);
+        closure.forget();
+    }
+    Ok(())
+}
+
+pub fn setup_resize_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "resize", Rc::new(|image_data| {
+        let filter: ResizeFilter = Select::new_from_id("resize_filter")?.value().parse()?;
+        let (width, height) = image_data.size();
+        let (target_width, target_height) = target_size((width, height))?;
+        Ok(Box::new(Resize::new(
+            image_data.data(), width, height, target_width, target_height, filter)?))
+    }))?;
+    setup_resize_dimension_event(editor)?;
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Input, Select, Text};
+use crate::filter::{Filter, Interpolation, Rotate, RotateFit, Deskew, parse_hex_color};
+use super::{BaseCanvas, Editor, Tool, ToolOverlay, lock_state};
+use super::filter::{FilterFactory, setup_filter_event, run_filter};
+
+const LINE_COLOR: &str = "#ff9800";
+/// Shorter lines in CSS pixels are taken as a click and ignored.
+const MIN_LINE_LENGTH: f64 = 4.0;
+
+#[derive(Debug, Default)]
+struct StraightenState {
+    /// Start of the line in canvas pixels while it is being drawn.
+    start: Option<(f64, f64)>,
+    image: BaseCanvas,
+}
+
+impl ToolOverlay for StraightenState {
+    /// Drops a line being drawn, as its start no longer matches the canvas.
+    fn redraw(&mut self, editor: &Editor) -> Result<()> {
+        self.start = None;
+        self.image.clear();
+        editor.draw_image_data()
+    }
+
+    fn reset(&mut self) -> Result<()> {
+        *self = Self::default();
+        Ok(())
+    }
+}
+
+/// Resampling, fit and fill color shared by rotation, straightening and deskew.
+fn rotate_options() -> Result<(Interpolation, RotateFit, [u8; 4])> {
+    let interpolation: Interpolation = Select::new_from_id("rotate_interpolation")?.value().parse()?;
+    let fit: RotateFit = Select::new_from_id("rotate_fit")?.value().parse()?;
+    let fill = if Input::new_from_id("rotate_transparent")?.checked() {
+        [0; 4]
+    } else {
+        let [r, g, b] = parse_hex_color(&Input::new_from_id("rotate_fill")?.value())?;
+        [r, g, b, 255]
+    };
+    Ok((interpolation, fit, fill))
+}
+
+fn rotate_factory(degrees: Option<f64>) -> FilterFactory {
+    Rc::new(move |image_data| {
+        let degrees = match degrees {
+            Some(degrees) => degrees,
+            None => Input::new_from_id("rotate_angle")?.value_as_number()?,
+        };
+        let (interpolation, fit, fill) = rotate_options()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(Rotate::new(
+            image_data.data(), width, height, degrees, interpolation, fit, fill)?))
+    })
+}
+
+/// Deskew that shows the detected angle once it has finished.
+struct ReportedDeskew(Deskew);
+
+impl ReportedDeskew {
+    fn report(&self) -> Result<()> {
+        let text = match (self.0.angle(), self.0.error()) {
+            (_, Some(err)) => err.to_string(),
+            (Some(angle), None) => format!("Detected skew {:.2}°", angle),
+            (None, None) => "No text found to measure the skew".to_string(),
+        };
+        Text::new_from_id("deskew_report")?.set_text(&text);
+        Ok(())
+    }
+}
+
+impl Filter for ReportedDeskew {
+    fn output_size(&self) -> (u32, u32) {
+        self.0.output_size()
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let done = self.0.step(data);
+        if done {
+            if let Err(err) = self.report() {
+                error!("{:#?}", err);
+            }
+        }
+        done
+    }
+}
+
+/// Angle in degrees of the line from `from` to `to`, folded into `-90..=90`.
+fn line_angle(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let angle = (to.1 - from.1).atan2(to.0 - from.0).to_degrees();
+    if angle > 90.0 {
+        angle - 180.0
+    } else if angle < -90.0 {
+        angle + 180.0
+    } else {
+        angle
+    }
+}
+
+/// Draws the cached image, and the line from the start to `end` when given.
+fn draw_line(editor: &Editor, state: &StraightenState, end: Option<(f64, f64)>) -> Result<()> {
+    editor.draw_over_image(|_, renderer| {
+        state.image.draw(renderer)?;
+        if let (Some(start), Some(end)) = (state.start, end) {
+            renderer.stroke_line(start, end, LINE_COLOR);
+        }
+        Ok(())
+    })
+}
+
+fn straighten_button_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<StraightenState>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let overlay = state.clone();
+    let mut state = lock_state(state)?;
+    state.start = None;
+    if editor.tool() == Tool::Straighten {
+        state.image.clear();
+        editor.set_tool(Tool::None)
+    } else {
+        editor.set_tool(Tool::Straighten)?;
+        editor.set_tool_overlay(overlay);
+        Ok(())
+    }
+}
+
+fn setup_straighten_button_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<StraightenState>>) -> Result<()> {
+    let button_element = Button::new_from_id("straighten")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = straighten_button_closure(&editor, &state) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+/// Returns the rotation that makes the finished line horizontal.
+fn finish_line(editor: &mut Editor, state: &mut StraightenState, end: (f64, f64)) -> Result<Option<f64>> {
+    let start = if let Some(start) = state.start.take() {
+        start
+    } else {
+        return Ok(None);
+    };
+    let length = (end.0 - start.0).hypot(end.1 - start.1);
+    if length < MIN_LINE_LENGTH * editor.pixel_ratio() {
+        draw_line(editor, state, None)?;
+        return Ok(None);
+    }
+    editor.set_tool(Tool::None)?;
+    draw_line(editor, state, None)?;
+    state.image.clear();
+    let degrees = -line_angle(start, end);
+    Input::new_from_id("rotate_angle")?.set_value(&format!("{:.2}", degrees));
+    Ok(Some(degrees))
+}
+
+fn straighten_canvas_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<StraightenState>>,
+                             event_name: &str, event: &Event) -> Result<()> {
+    let degrees = {
+        let mut state = lock_state(state)?;
+        if event_name != "pointerdown" && state.start.is_none() {
+            return Ok(());
+        }
+        let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+            editor
+        } else {
+            return Ok(());
+        };
+        if editor.tool() != Tool::Straighten {
+            return Ok(());
+        }
+        let canvas = Canvas::new_from_element(browser::canvas()?);
+        let position = canvas.event_position(event)?;
+        match event_name {
+            "pointerdown" => {
+                if let Some(image_data) = editor.get_image_data() {
+                    state.image.set(image_data)?;
+                    state.start = Some(position);
+                }
+                return Ok(());
+            },
+            "pointermove" => {
+                draw_line(&editor, &state, Some(position))?;
+                return Ok(());
+            },
+            "pointerup" => finish_line(&mut editor, &mut state, position)?,
+            _ => {
+                state.start = None;
+                draw_line(&editor, &state, None)?;
+                return Ok(());
+            },
+        }
+    };
+    if let Some(degrees) = degrees {
+        run_filter(editor.clone(), rotate_factory(Some(degrees)))?;
+    }
+    Ok(())
+}
+
+fn setup_straighten_canvas_event(editor: Rc<Mutex<Editor>>, state: Rc<Mutex<StraightenState>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    for event_name in ["pointerdown", "pointermove", "pointerup", "pointerleave"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = straighten_canvas_closure(&editor, &state, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+pub fn setup_rotate_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let state = Rc::new(Mutex::new(StraightenState::default()));
+    setup_filter_event(editor.clone(), "rotate", rotate_factory(None))?;
+    setup_filter_event(editor.clone(), "deskew", Rc::new(|image_data| {
+        let max_degrees = Input::new_from_id("deskew_max_angle")?.value_as_number()?;
+        let (interpolation, fit, fill) = rotate_options()?;
+        let (width, height) = image_data.size();
+        let deskew = Deskew::new(
+            image_data.data(), width, height, max_degrees, interpolation, fit, fill)?;
+        Text::new_from_id("deskew_report")?.set_text("Measuring the skew");
+        Ok(Box::new(ReportedDeskew(deskew)))
+    }))?;
+    setup_straighten_button_event(editor.clone(), state.clone())?;
+    setup_straighten_canvas_event(editor, state)?;
+    Ok(())
+}
@@ -5,7 +5,7 @@ use web_sys::Event;
 
 use crate::browser;
 use crate::engine::{
-    Button, Renderer, Canvas, Anchor,
+    Button, Renderer, Canvas, Anchor, Text,
 };
 use super::Editor;
 
@@ -40,7 +40,60 @@ fn setup_save_event_closure(editor: Rc<Mutex<Editor>>) -> Result<()> {
     Ok(())
 }
 
-pub fn setup_save_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+fn setup_copy_event_closure(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    if Editor::try_run(&editor).is_none() {
+        return Ok(());
+    }
+    let mut editor = Editor::lock(&editor)?;
+    let image = if let Some(image) = editor.get_image_data() {
+        image
+    } else {
+        log!("No image to copy");
+        editor.to_idle();
+        return Ok(());
+    };
+    let copy_canvas = Canvas::new_from_image_data(image)?;
+    let report = Text::new_from_id("copy_report")?;
+    // The write only continues in the background, so the editor is not kept busy for it.
+    let copy = copy_canvas.copy_to_clipboard();
+    editor.to_idle();
+    let copy = match copy {
+        Ok(copy) => copy,
+        Err(err) => {
+            report.set_text("Copying images is not allowed by this browser, use Save instead");
+            return Err(err);
+        },
+    };
+    report.set_text("Copying...");
+    browser::spawn_local(async move {
+        match copy.await {
+            Ok(()) => report.set_text("Copied to the clipboard"),
+            Err(err) => {
+                report.set_text("Copying images is not allowed by this browser, use Save instead");
+                error!("{:#?}", err);
+            },
+        }
+    });
+    Ok(())
+}
+
+fn setup_copy_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let button_element = Button::new_from_id("copy")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = setup_copy_event_closure(editor_clone) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn setup_save_button_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
     let button_element = Button::new_from_id("save")?;
 
     let closure = browser::create_event_closure(move |_event: Event| {
@@ -55,3 +108,9 @@ pub fn setup_save_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
 
     Ok(())
 }
+
+pub fn setup_save_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_save_button_event(editor.clone())?;
+    setup_copy_event(editor)?;
+    Ok(())
+}
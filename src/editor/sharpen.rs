@@ -0,0 +1,37 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::Input;
+use crate::filter::{Convolution, KernelPreset, UnsharpMask};
+use super::Editor;
+use super::filter::{setup_filter_event, FilterFactory};
+use super::preview::setup_preview_event;
+
+fn sharpen_factory() -> FilterFactory {
+    Rc::new(|image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(Convolution::new_from_preset(
+            KernelPreset::Sharpen, image_data.data(), width, height)?))
+    })
+}
+
+fn unsharp_mask_factory() -> FilterFactory {
+    Rc::new(|image_data| {
+        let amount = Input::new_from_id("unsharp_amount")?.value_as_number()?;
+        let radius = Input::new_from_id("unsharp_radius")?.value_as_number()?;
+        let threshold = Input::new_from_id("unsharp_threshold")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(UnsharpMask::new(
+            image_data.data(), width, height,
+            amount / 100.0, radius as u32, threshold.clamp(0.0, 255.0) as u8)?))
+    })
+}
+
+pub fn setup_sharpen_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "sharpen", sharpen_factory())?;
+    setup_preview_event(editor.clone(), "sharpen_preview", sharpen_factory())?;
+    setup_filter_event(editor.clone(), "unsharp_mask", unsharp_mask_factory())?;
+    setup_preview_event(editor, "unsharp_mask_preview", unsharp_mask_factory())?;
+    Ok(())
+}
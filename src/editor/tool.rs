@@ -0,0 +1,11 @@
+/// Interactive tool that receives the mouse events on the canvas.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tool {
+    None,
+    NeutralPick,
+    Crop,
+    Straighten,
+    Perspective,
+    Compare,
+    Eyedropper,
+}
@@ -0,0 +1,24 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+
+use crate::engine::{Input, Select, Text};
+use crate::filter::{TrimMode, Trim};
+use super::Editor;
+use super::filter::setup_filter_event;
+
+pub fn setup_trim_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor, "trim", Rc::new(|image_data| {
+        let mode: TrimMode = Select::new_from_id("trim_mode")?.value().parse()?;
+        let tolerance = Input::new_from_id("trim_tolerance")?.value_as_number()?;
+        let margin = Input::new_from_id("trim_margin")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        let trim = Trim::new(
+            image_data.data(), width, height, mode, tolerance.clamp(0.0, 255.0) as u8, margin as u32)?;
+        let trimmed = trim.trimmed();
+        Text::new_from_id("trim_report")?.set_text(&format!(
+            "Trimmed top {}, bottom {}, left {}, right {} px",
+            trimmed.top, trimmed.bottom, trimmed.left, trimmed.right));
+        Ok(Box::new(trim))
+    }))
+}
@@ -0,0 +1,291 @@
+use std::rc::Rc;
+use std::cell::RefCell;
+use std::sync::Mutex;
+use anyhow::{Result, anyhow};
+use web_sys::{Event, MediaQueryList};
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Text, step_zoom};
+use super::{BaseCanvas, Editor, Tool, lock_state};
+
+/// Zoom factor per pixel of wheel scrolling.
+const WHEEL_ZOOM_SPEED: f64 = 0.0015;
+
+#[derive(Debug, Default)]
+struct ViewportState {
+    /// Pointers pressed on the canvas with their last position in canvas pixels.
+    pointers: Vec<(i32, (f64, f64))>,
+    image: BaseCanvas,
+}
+
+/// Writes the current zoom next to the zoom buttons.
+pub fn show_zoom(editor: &Editor) -> Result<()> {
+    let text = match (editor.zoom_scale(), editor.viewport_zoom()) {
+        (Some(scale), None) => format!("Fit ({:.0}%)", scale * 100.0),
+        (Some(scale), Some(_)) => format!("{:.0}%", scale * 100.0),
+        (None, _) => String::new(),
+    };
+    Text::new_from_id("zoom_level")?.set_text(&text);
+    Ok(())
+}
+
+fn zoom_button_closure(editor: &Rc<Mutex<Editor>>, zoom: &dyn Fn(&Editor) -> Option<f64>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let zoom = zoom(&editor);
+    editor.set_zoom(zoom);
+    editor.redraw_with_tool()?;
+    show_zoom(&editor)
+}
+
+fn setup_zoom_button_event<F>(editor: Rc<Mutex<Editor>>, button_id: &str, zoom: F) -> Result<()>
+where
+    F: Fn(&Editor) -> Option<f64> + 'static,
+{
+    let button_element = Button::new_from_id(button_id)?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = zoom_button_closure(&editor, &zoom) {
+            error!("{:#?}", err);
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn wheel_closure(editor: &Rc<Mutex<Editor>>, event: &Event) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let scale = match editor.zoom_scale() {
+        Some(scale) if editor.tool() == Tool::None => scale,
+        _ => return Ok(()),
+    };
+    event.prevent_default();
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let position = canvas.event_position(event)?;
+    let delta = browser::wheel_event_delta_y(event)?;
+    editor.zoom_at(scale * (-delta * WHEEL_ZOOM_SPEED).exp(), position);
+    editor.draw_image_data()?;
+    show_zoom(&editor)
+}
+
+fn setup_wheel_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    let closure = browser::create_event_closure(move |event: Event| {
+        if let Err(err) = wheel_closure(&editor, &event) {
+            error!("{:#?}", err);
+        }
+    });
+
+    canvas.add_event_listener_with_callback("wheel", &closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn midpoint(a: (f64, f64), b: (f64, f64)) -> (f64, f64) {
+    ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+}
+
+/// Pans with one pointer, and zooms around the fingers while panning with two.
+fn move_pointer(editor: &mut Editor, state: &mut ViewportState, id: i32, position: (f64, f64)) -> Result<()> {
+    let index = if let Some(index) = state.pointers.iter().position(|(pointer, _)| *pointer == id) {
+        index
+    } else {
+        return Ok(());
+    };
+    let previous = state.pointers[index].1;
+    state.pointers[index].1 = position;
+    match state.pointers.as_slice() {
+        [_] => editor.pan_by((position.0 - previous.0, position.1 - previous.1)),
+        [(_, first), (_, second)] => {
+            let (before_first, before_second) = if index == 0 {
+                (previous, *second)
+            } else {
+                (*first, previous)
+            };
+            let before = distance(before_first, before_second);
+            let scale = editor.zoom_scale().unwrap_or(1.0);
+            if before > 0.0 {
+                let center = midpoint(before_first, before_second);
+                editor.zoom_at(scale * distance(*first, *second) / before, center);
+            }
+            let center = midpoint(*first, *second);
+            let before_center = midpoint(before_first, before_second);
+            editor.pan_by((center.0 - before_center.0, center.1 - before_center.1));
+        },
+        _ => return Ok(()),
+    }
+    editor.draw_over_image(|_, renderer| state.image.draw(renderer))?;
+    show_zoom(editor)
+}
+
+fn viewport_canvas_closure(editor: &Rc<Mutex<Editor>>, state: &Rc<Mutex<ViewportState>>,
+                           event_name: &str, event: &Event) -> Result<()> {
+    let mut state = lock_state(state)?;
+    if event_name != "pointerdown" && state.pointers.is_empty() {
+        return Ok(());
+    }
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    let id = browser::pointer_event_id(event)?;
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let position = canvas.event_position(event)?;
+    match event_name {
+        "pointerdown" => {
+            if editor.tool() != Tool::None || state.pointers.len() >= 2 {
+                return Ok(());
+            }
+            if let Some(image_data) = editor.get_image_data() {
+                if state.pointers.is_empty() {
+                    state.image.set(image_data)?;
+                }
+                state.pointers.push((id, position));
+            }
+        },
+        "pointermove" => move_pointer(&mut editor, &mut state, id, position)?,
+        _ => {
+            state.pointers.retain(|(pointer, _)| *pointer != id);
+            if state.pointers.is_empty() {
+                state.image.clear();
+            }
+        },
+    }
+    Ok(())
+}
+
+fn setup_viewport_canvas_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+    let state = Rc::new(Mutex::new(ViewportState::default()));
+
+    for event_name in ["pointerdown", "pointermove", "pointerup", "pointercancel", "pointerleave"] {
+        let editor = editor.clone();
+        let state = state.clone();
+        let closure = browser::create_event_closure(move |event: Event| {
+            if let Err(err) = viewport_canvas_closure(&editor, &state, event_name, &event) {
+                error!("{:#?}", err);
+            }
+        });
+        canvas.add_event_listener_with_callback(event_name, &closure)?;
+        closure.forget();
+    }
+
+    Ok(())
+}
+
+/// Resizes the backing store to the displayed canvas and draws the image again.
+fn resize_canvas(editor: &mut Editor) -> Result<()> {
+    editor.update_canvas_size()?;
+    editor.redraw_with_tool()?;
+    show_zoom(editor)
+}
+
+fn resize_closure(editor: &Rc<Mutex<Editor>>) -> Result<()> {
+    let mut editor = if let Some(editor) = Editor::try_lock(editor) {
+        editor
+    } else {
+        return Ok(());
+    };
+    resize_canvas(&mut editor)
+}
+
+fn setup_resize_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = resize_closure(&editor) {
+            error!("{:#?}", err);
+        }
+    });
+
+    browser::add_event_listener_with_callback_window("resize", &closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+/// The one listener on the query for the current device pixel ratio.
+#[derive(Default)]
+struct PixelRatioWatch {
+    media_query_list: Option<MediaQueryList>,
+    closure: Option<browser::EventClosure>,
+}
+
+impl PixelRatioWatch {
+    /// Moves the listener from the query for the previous ratio to the current one.
+    fn watch_current_ratio(&mut self) -> Result<()> {
+        let closure = self.closure.as_ref()
+            .ok_or_else(|| anyhow!("No pixel ratio closure"))?;
+        if let Some(media_query_list) = self.media_query_list.take() {
+            browser::remove_listener_with_opt_callback(&media_query_list, Some(closure))?;
+        }
+        let media_query_list = browser::get_pixel_ratio_media_query_list(browser::device_pixel_ratio()?)?;
+        browser::add_listener_with_opt_callback(&media_query_list, Some(closure))?;
+        self.media_query_list = Some(media_query_list);
+        Ok(())
+    }
+}
+
+/// Resizes for the new ratio, or tries again later while the editor is busy.
+fn pixel_ratio_resize(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    if let Some(mut editor) = Editor::try_lock(&editor) {
+        return resize_canvas(&mut editor);
+    }
+    browser::set_callback_once(move || {
+        if let Err(err) = pixel_ratio_resize(editor) {
+            error!("{:#?}", err);
+        }
+    })
+}
+
+fn pixel_ratio_closure(editor: Rc<Mutex<Editor>>, watch: &RefCell<PixelRatioWatch>) -> Result<()> {
+    watch.borrow_mut().watch_current_ratio()?;
+    pixel_ratio_resize(editor)
+}
+
+/// Watches the current device pixel ratio, which changes with the browser zoom or between screens.
+fn setup_pixel_ratio_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let watch = Rc::new(RefCell::new(PixelRatioWatch::default()));
+    let watch_clone = watch.clone();
+    // The closure owns the watch holding it, so both live as long as the page.
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Err(err) = pixel_ratio_closure(editor.clone(), &watch_clone) {
+            error!("{:#?}", err);
+        }
+    });
+    let mut watch = watch.borrow_mut();
+    watch.closure = Some(closure);
+    watch.watch_current_ratio()
+}
+
+pub fn setup_viewport_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_zoom_button_event(editor.clone(), "zoom_fit", |_| None)?;
+    setup_zoom_button_event(editor.clone(), "zoom_100", |_| Some(1.0))?;
+    setup_zoom_button_event(editor.clone(), "zoom_200", |_| Some(2.0))?;
+    setup_zoom_button_event(editor.clone(), "zoom_in", |editor| {
+        editor.zoom_scale().map(|scale| step_zoom(scale, true))
+    })?;
+    setup_zoom_button_event(editor.clone(), "zoom_out", |editor| {
+        editor.zoom_scale().map(|scale| step_zoom(scale, false))
+    })?;
+    setup_wheel_event(editor.clone())?;
+    setup_resize_event(editor.clone())?;
+    setup_pixel_ratio_event(editor.clone())?;
+    setup_viewport_canvas_event(editor)?;
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use std::rc::Rc;
+use std::sync::Mutex;
+use anyhow::Result;
+use web_sys::Event;
+
+use crate::browser;
+use crate::engine::{Button, Canvas, Input};
+use crate::filter::{WhiteBalance, parse_hex_color};
+use super::{Editor, Tool};
+use super::filter::{setup_filter_event, run_filter};
+
+fn setup_neutral_pick_button_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let button_element = Button::new_from_id("neutral_pick")?;
+
+    let closure = browser::create_event_closure(move |_event: Event| {
+        if let Some(mut editor) = Editor::try_lock(&editor) {
+            let tool = if editor.tool() == Tool::NeutralPick {
+                Tool::None
+            } else {
+                Tool::NeutralPick
+            };
+            if let Err(err) = editor.set_tool(tool) {
+                error!("{:#?}", err);
+            }
+        }
+    });
+
+    button_element.add_event_listener_with_callback(&closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+fn setup_neutral_pick_canvas_event_closure(editor: Rc<Mutex<Editor>>, event: Event) -> Result<()> {
+    let (x, y) = {
+        let mut editor = if let Some(editor) = Editor::try_lock(&editor) {
+            editor
+        } else {
+            return Ok(());
+        };
+        if editor.tool() != Tool::NeutralPick {
+            return Ok(());
+        }
+        let canvas = Canvas::new_from_element(browser::canvas()?);
+        let (canvas_x, canvas_y) = canvas.event_position(&event)?;
+        if let Some(position) = editor.canvas_to_image_position(canvas_x, canvas_y) {
+            editor.set_tool(Tool::None)?;
+            position
+        } else {
+            return Ok(());
+        }
+    };
+    run_filter(editor, Rc::new(move |image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(WhiteBalance::neutral_point(image_data.data(), width, height, x, y)?))
+    }))
+}
+
+fn setup_neutral_pick_canvas_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    let canvas = Canvas::new_from_element(browser::canvas()?);
+
+    let closure = browser::create_event_closure(move |event: Event| {
+        let editor_clone = editor.clone();
+        if let Err(err) = setup_neutral_pick_canvas_event_closure(editor_clone, event) {
+            error!("{:#?}", err);
+        }
+    });
+
+    canvas.add_event_listener_with_callback("click", &closure)?;
+    closure.forget();
+
+    Ok(())
+}
+
+pub fn setup_white_balance_event(editor: Rc<Mutex<Editor>>) -> Result<()> {
+    setup_filter_event(editor.clone(), "gray_world", Rc::new(|image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(WhiteBalance::gray_world(image_data.data(), width, height)))
+    }))?;
+    setup_filter_event(editor.clone(), "white_patch", Rc::new(|image_data| {
+        let (width, height) = image_data.size();
+        Ok(Box::new(WhiteBalance::white_patch(image_data.data(), width, height)))
+    }))?;
+    setup_filter_event(editor.clone(), "percentile_stretch", Rc::new(|image_data| {
+        let percent = Input::new_from_id("stretch_percent")?.value_as_number()?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(WhiteBalance::percentile_stretch(
+            image_data.data(), width, height, percent)?))
+    }))?;
+    setup_filter_event(editor.clone(), "picked_neutral", Rc::new(|image_data| {
+        let color = parse_hex_color(&Input::new_from_id("picked_color")?.value())?;
+        let (width, height) = image_data.size();
+        Ok(Box::new(WhiteBalance::neutral_color(width, height, color)))
+    }))?;
+    setup_neutral_pick_button_event(editor.clone())?;
+    setup_neutral_pick_canvas_event(editor)?;
+    Ok(())
+}
@@ -7,8 +7,12 @@ mod anchor;
 mod canvas;
 mod rect;
 mod label;
+mod select;
+mod text_area;
+mod text;
+mod viewport;
 
-pub use renderer::Renderer;
+pub use renderer::{Renderer, Comparison};
 pub use image::Image;
 pub use input::Input;
 pub use image_data::ImageDataWrapper;
@@ -17,6 +21,10 @@ pub use anchor::Anchor;
 pub use canvas::Canvas;
 pub use rect::Rect;
 pub use label::Label;
+pub use select::Select;
+pub use text_area::TextArea;
+pub use text::Text;
+pub use viewport::{Viewport, step_zoom};
 
 pub trait DisplayElement: std::fmt::Debug {
     fn set_disabled(&self, disabled: bool);
@@ -1,8 +1,10 @@
-use anyhow::Result;
+use std::future::Future;
+use anyhow::{Result, anyhow};
 use web_sys::{
-    HtmlCanvasElement, CanvasRenderingContext2d
+    HtmlCanvasElement, CanvasRenderingContext2d, Event,
 };
 use crate::browser;
+use super::{Image, ImageDataWrapper, Rect, Renderer};
 
 #[derive(Debug)]
 pub struct Canvas {
@@ -17,16 +19,39 @@ impl Canvas {
         })
     }
 
+    /// Creates a canvas of the image size with the image drawn on it.
+    pub fn new_from_image_data(image_data: &ImageDataWrapper) -> Result<Self> {
+        let (width, height) = image_data.size();
+        let canvas = Self::new(width, height)?;
+        Renderer::create_from_canvas(&canvas)?.draw_image_data(image_data)?;
+        Ok(canvas)
+    }
+
+    /// Creates a canvas of the image size with the image drawn on it.
+    pub fn new_from_image(image: &Image) -> Result<Self> {
+        let (width, height) = image.size();
+        let canvas = Self::new(width, height)?;
+        Renderer::create_from_canvas(&canvas)?
+            .draw_image(image, &Rect::new(0.0, 0.0, width as f64, height as f64))?;
+        Ok(canvas)
+    }
+
     pub fn new_from_element(canvas: HtmlCanvasElement) -> Self {
         Self {
             canvas,
         }
     }
 
+    pub fn element(&self) -> &HtmlCanvasElement {
+        &self.canvas
+    }
+
+    /// Sizes the backing store to the displayed size in device pixels, so it is drawn sharp.
     pub fn set_canvas_size_from_display_size(&self) -> Result<()> {
         let (width, height) = browser::get_canvas_display_size(&self.canvas);
-        self.canvas.set_width(width as u32);
-        self.canvas.set_height(height as u32);
+        let ratio = browser::device_pixel_ratio()?;
+        self.canvas.set_width((width * ratio).round() as u32);
+        self.canvas.set_height((height * ratio).round() as u32);
         Ok(())
     }
 
@@ -41,4 +66,41 @@ impl Canvas {
     pub fn to_data_url(&self) -> Result<String> {
         browser::canvas_to_data_url(&self.canvas)
     }
+
+    /// Starts copying the canvas as a PNG to the clipboard, finished by the returned future.
+    pub fn copy_to_clipboard(&self) -> Result<impl Future<Output = Result<()>>> {
+        let write = browser::clipboard_write_png(&browser::canvas_to_png_blob(&self.canvas))?;
+        Ok(async move {
+            write.await
+                .map(|_| ())
+                .map_err(|err| anyhow!("Could not copy to the clipboard {:#?}", err))
+        })
+    }
+
+    pub fn add_event_listener_with_callback(
+        &self,
+        event_name: &str,
+        closure: &browser::EventClosure,
+    ) -> Result<()> {
+        browser::add_event_listener_with_callback_canvas(
+            &self.canvas,
+            event_name,
+            closure,
+        )
+    }
+
+    pub fn set_class(&self, class: &str, enabled: bool) -> Result<()> {
+        browser::set_canvas_class(&self.canvas, class, enabled)
+    }
+
+    /// Position of a mouse event in canvas pixels.
+    pub fn event_position(&self, event: &Event) -> Result<(f64, f64)> {
+        let (x, y) = browser::mouse_event_offset(event)?;
+        let (display_width, display_height) = browser::get_canvas_display_size(&self.canvas);
+        let (width, height) = self.size();
+        if display_width <= 0.0 || display_height <= 0.0 {
+            return Ok((x, y));
+        }
+        Ok((x * width as f64 / display_width, y * height as f64 / display_height))
+    }
 }
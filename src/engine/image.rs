@@ -6,6 +6,25 @@ use wasm_bindgen::{JsCast, JsValue, closure::Closure};
 use web_sys::HtmlImageElement;
 use crate::browser;
 
+pub fn calculate_fitted_size(
+    size: (u32, u32),
+    max_width: f64,
+    max_height: f64,
+) -> (f64, f64) {
+    let mut width = size.0 as f64;
+    let mut height = size.1 as f64;
+    let ratio = width / height;
+    if width > max_width {
+        width = max_width;
+        height = width / ratio;
+    }
+    if height > max_height {
+        height = max_height;
+        width = height * ratio;
+    }
+    (width, height)
+}
+
 #[derive(Debug)]
 pub struct Image {
     element: HtmlImageElement,
@@ -29,25 +48,6 @@ impl Image {
         self.size
     }
 
-    pub fn calculate_fitted_size(
-        &self,
-        max_width: f64,
-        max_height: f64,
-    ) -> (f64, f64) {
-        let mut width = self.size.0 as f64;
-        let mut height = self.size.1 as f64;
-        let ratio = width / height;
-        if width > max_width {
-            width = max_width;
-            height = width / ratio;
-        }
-        if height > max_height {
-            height = max_height;
-            width = height * ratio;
-        }
-        (width, height)
-    }
-
     pub async fn load_image(source: &str) -> Result<Self> {
         let image = browser::new_image()?;
 
@@ -3,9 +3,6 @@ use web_sys::{
     ImageData, CanvasRenderingContext2d,
 };
 use crate::browser;
-use super::{
-    Image, Canvas, Renderer,
-};
 
 #[derive(Debug, Clone)]
 pub struct ImageDataWrapper {
@@ -28,12 +25,26 @@ impl ImageDataWrapper {
         }
     }
 
+    pub fn new_from_data(data: Vec<u8>, width: u32, height: u32) -> Result<Self> {
+        let image_data = browser::image_data(&data, width, height)?;
+        Ok(Self {
+            image_data,
+            width,
+            height,
+            data,
+        })
+    }
+
     pub fn new_from_context(context: &CanvasRenderingContext2d,
                             x: u32, y: u32, width: u32, height: u32) -> Result<Self> {
         browser::get_context_image_data(context, x, y, width, height)
             .map(Self::new)
     }
 
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn data_mut(&mut self) -> &mut [u8] {
         &mut self.data
     }
@@ -54,14 +65,4 @@ impl ImageDataWrapper {
         self.image_data = browser::image_data(&self.data, self.width, self.height)?;
         Ok(())
     }
-
-    pub async fn to_image(&self) -> Result<Image> {
-        let canvas = Canvas::new(self.width, self.height)?;
-        let render = Renderer::create_from_canvas(&canvas)?;
-        render.draw_image_data(&self)?;
-
-        let data_url = canvas.to_data_url()?;
-
-        Image::load_image(&data_url).await
-    }
 }
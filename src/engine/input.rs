@@ -36,6 +36,27 @@ impl Input {
         self.element.set_onchange(Some(closure.as_ref().unchecked_ref()));
     }
 
+    pub fn value(&self) -> String {
+        self.element.value()
+    }
+
+    pub fn set_value(&self, value: &str) {
+        self.element.set_value(value);
+    }
+
+    pub fn checked(&self) -> bool {
+        self.element.checked()
+    }
+
+    pub fn value_as_number(&self) -> Result<f64> {
+        let value = self.element.value_as_number();
+        if value.is_nan() {
+            Err(anyhow!("Input {} is not a number", self.element.id()))
+        } else {
+            Ok(value)
+        }
+    }
+
     pub fn get_first_image_file(&self) -> Result<Option<File>> {
         let files = self.element.files();
         if let Some(files) = files {
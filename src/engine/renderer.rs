@@ -4,11 +4,29 @@ use crate::browser;
 use super::{
     Image, ImageDataWrapper, Canvas, Rect,
 };
+use super::viewport::{Viewport, PIXEL_GRID_ZOOM};
+
+const PIXEL_GRID_COLOR: &str = "rgba(128, 128, 128, 0.5)";
+const COMPARISON_LINE_COLOR: &str = "#ffffff";
+
+/// How an earlier version of the image is shown next to the current one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// The earlier version left of the given canvas x and the current one right of it.
+    Split(f64),
+    /// The earlier version in the left half of the canvas and the current one in the right.
+    SideBySide,
+    /// Only the earlier version.
+    Reference,
+}
 
 #[derive(Debug)]
 pub struct Renderer {
     context: CanvasRenderingContext2d,
     size: (u32, u32),
+    /// Device pixels per CSS pixel, to keep lines and handles the same size on every screen.
+    pixel_ratio: f64,
+    viewport: Viewport,
 }
 
 impl Renderer {
@@ -20,6 +38,8 @@ impl Renderer {
         Ok(Self {
             context,
             size: (width, height),
+            pixel_ratio: browser::device_pixel_ratio()?,
+            viewport: Viewport::default(),
         })
     }
 
@@ -29,6 +49,8 @@ impl Renderer {
         Ok(Self {
             context,
             size: (width, height),
+            pixel_ratio: 1.0,
+            viewport: Viewport::default(),
         })
     }
 
@@ -40,9 +62,18 @@ impl Renderer {
         let canvas = Canvas::new_from_element(browser::canvas()?);
         canvas.set_canvas_size_from_display_size()?;
         self.size = canvas.size();
+        self.pixel_ratio = browser::device_pixel_ratio()?;
         Ok(())
     }
 
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub fn pixel_ratio(&self) -> f64 {
+        self.pixel_ratio
+    }
+
     pub fn get_draw_image_rect(&self, width: f64, height: f64) -> Rect {
         let (canvas_w, canvas_h) = self.size;
         let mut rect = Rect::new(0.0, 0.0, width, height);
@@ -50,24 +81,157 @@ impl Renderer {
         rect
     }
 
+    /// Rectangle the image covers on the canvas with the current zoom and pan.
+    pub fn get_image_rect(&self, image_size: (u32, u32)) -> Rect {
+        self.viewport.image_rect(image_size, self.size)
+    }
+
+    pub fn viewport(&self) -> &Viewport {
+        &self.viewport
+    }
+
+    /// Canvas pixels per image pixel with the current zoom.
+    pub fn zoom_scale(&self, image_size: (u32, u32)) -> f64 {
+        self.viewport.scale(image_size, self.size)
+    }
+
+    pub fn set_zoom(&mut self, zoom: Option<f64>) {
+        self.viewport.set_zoom(zoom);
+    }
+
+    pub fn zoom_at(&mut self, image_size: (u32, u32), zoom: f64, anchor: (f64, f64)) {
+        self.viewport.zoom_at(image_size, self.size, zoom, anchor);
+    }
+
+    pub fn pan_by(&mut self, image_size: (u32, u32), delta: (f64, f64)) {
+        self.viewport.pan_by(image_size, self.size, delta);
+    }
+
+    /// Maps a canvas position to image coordinates, which may lie outside of the image.
+    pub fn canvas_to_image_point(&self, x: f64, y: f64, image_size: (u32, u32)) -> Option<(f64, f64)> {
+        let rect = self.get_image_rect(image_size);
+        if rect.width <= 0.0 || rect.height <= 0.0 {
+            return None;
+        }
+        Some((
+            (x - rect.x) * image_size.0 as f64 / rect.width,
+            (y - rect.y) * image_size.1 as f64 / rect.height,
+        ))
+    }
+
+    /// Maps a canvas position to the pixel of an image drawn with the current viewport.
+    pub fn canvas_to_image_position(&self, x: f64, y: f64, image_size: (u32, u32)) -> Option<(u32, u32)> {
+        let (image_x, image_y) = self.canvas_to_image_point(x, y, image_size)?;
+        if image_x < 0.0 || image_y < 0.0 ||
+                image_x >= image_size.0 as f64 || image_y >= image_size.1 as f64 {
+            return None;
+        }
+        Some((image_x as u32, image_y as u32))
+    }
+
+    /// Maps a rectangle in image pixels to the canvas, the inverse of `canvas_to_image_position`.
+    pub fn image_to_canvas_rect(&self, image_size: (u32, u32), rect: &Rect) -> Rect {
+        let fitted = self.get_image_rect(image_size);
+        let scale_x = fitted.width / image_size.0 as f64;
+        let scale_y = fitted.height / image_size.1 as f64;
+        Rect::new(
+            fitted.x + rect.x * scale_x,
+            fitted.y + rect.y * scale_y,
+            rect.width * scale_x,
+            rect.height * scale_y,
+        )
+    }
+
     pub fn clear(&self) {
         self.context.clear_rect(
             0.0, 0.0, self.size.0 as f64, self.size.1 as f64);
     }
 
+    /// Uses nearest neighbor sampling when the source is drawn enlarged.
+    fn set_smoothing(&self, source_width: u32, rect: &Rect) {
+        self.context.set_image_smoothing_enabled(rect.width <= source_width as f64);
+    }
+
     pub fn draw_image(&self, image: &Image, rect: &Rect) -> Result<()> {
+        self.set_smoothing(image.size().0, rect);
         self.context.draw_image_with_html_image_element_and_dw_and_dh(
             image.element(), rect.x, rect.y, rect.width, rect.height)
             .map_err(|err| anyhow!("Could not draw image {:#?}", err))
             .map(|_| ())
     }
 
+    pub fn draw_canvas(&self, canvas: &Canvas, rect: &Rect) -> Result<()> {
+        self.set_smoothing(canvas.size().0, rect);
+        self.context.draw_image_with_html_canvas_element_and_dw_and_dh(
+            canvas.element(), rect.x, rect.y, rect.width, rect.height)
+            .map_err(|err| anyhow!("Could not draw canvas {:#?}", err))
+            .map(|_| ())
+    }
+
+    pub fn stroke_rect(&self, rect: &Rect, color: &str) {
+        self.context.set_stroke_style(&color.into());
+        self.context.set_line_width(self.pixel_ratio);
+        self.context.stroke_rect(rect.x, rect.y, rect.width, rect.height);
+    }
+
+    pub fn stroke_line(&self, from: (f64, f64), to: (f64, f64), color: &str) {
+        self.context.set_stroke_style(&color.into());
+        self.context.set_line_width(self.pixel_ratio);
+        self.context.begin_path();
+        self.context.move_to(from.0, from.1);
+        self.context.line_to(to.0, to.1);
+        self.context.stroke();
+    }
+
+    pub fn fill_rect(&self, rect: &Rect, color: &str) {
+        self.context.set_fill_style(&color.into());
+        self.context.fill_rect(rect.x, rect.y, rect.width, rect.height);
+    }
+
+    /// Draws lines between the image pixels once they are large enough to tell apart.
+    pub fn draw_pixel_grid(&self, image_size: (u32, u32)) {
+        if self.zoom_scale(image_size) < PIXEL_GRID_ZOOM {
+            return;
+        }
+        let rect = self.get_image_rect(image_size);
+        let (width, height) = (self.size.0 as f64, self.size.1 as f64);
+        let scale = rect.width / image_size.0 as f64;
+        let first_x = ((-rect.x / scale).ceil().max(0.0)) as u32;
+        let last_x = (((width - rect.x) / scale).floor() as u32).min(image_size.0);
+        let first_y = ((-rect.y / scale).ceil().max(0.0)) as u32;
+        let last_y = (((height - rect.y) / scale).floor() as u32).min(image_size.1);
+        let top = rect.y.max(0.0);
+        let bottom = (rect.y + rect.height).min(height);
+        let left = rect.x.max(0.0);
+        let right = (rect.x + rect.width).min(width);
+        self.context.set_stroke_style(&PIXEL_GRID_COLOR.into());
+        self.context.set_line_width(1.0);
+        self.context.begin_path();
+        for x in first_x..=last_x {
+            let x = (rect.x + x as f64 * scale).round() + 0.5;
+            self.context.move_to(x, top);
+            self.context.line_to(x, bottom);
+        }
+        for y in first_y..=last_y {
+            let y = (rect.y + y as f64 * scale).round() + 0.5;
+            self.context.move_to(left, y);
+            self.context.line_to(right, y);
+        }
+        self.context.stroke();
+    }
+
     pub fn draw_image_fit_canvas(&self, image: &Image) -> Result<()> {
-        let (width, height) = self.size;
-        let (dw, dh) = image
-            .calculate_fitted_size(width as f64, height as f64);
-        let rect = self.get_draw_image_rect(dw, dh);
-        self.draw_image(image, &rect)
+        self.draw_image(image, &self.get_image_rect(image.size()))?;
+        self.draw_pixel_grid(image.size());
+        Ok(())
+    }
+
+    /// Clears the canvas and draws a canvas holding the image in the viewport.
+    pub fn draw_canvas_in_view(&self, canvas: &Canvas) -> Result<()> {
+        self.clear();
+        self.draw_canvas(canvas, &self.get_image_rect(canvas.size()))?;
+        self.draw_pixel_grid(canvas.size());
+        Ok(())
     }
 
     pub fn draw_image_data(&self, image_data: &ImageDataWrapper) -> Result<()> {
@@ -79,9 +243,45 @@ impl Renderer {
             .map(|_| ())
     }
 
-    pub async fn draw_image_data_fit_canvas(&self, image_data: &ImageDataWrapper) -> Result<()> {
-        let image = image_data.to_image().await?;
+    /// Draws `canvas` at `rect`, showing only the part inside of `clip`.
+    fn draw_canvas_clipped(&self, canvas: &Canvas, rect: &Rect, clip: &Rect) -> Result<()> {
+        self.context.save();
+        self.context.begin_path();
+        self.context.rect(clip.x, clip.y, clip.width, clip.height);
+        self.context.clip();
+        let result = self.draw_canvas(canvas, rect);
+        self.context.restore();
+        result
+    }
+
+    /// Draws the current image and an earlier version of it, each placed by the viewport.
+    pub fn draw_comparison(&self, current: &Canvas, reference: &Canvas, comparison: Comparison) -> Result<()> {
+        let (width, height) = (self.size.0 as f64, self.size.1 as f64);
         self.clear();
-        self.draw_image_fit_canvas(&image)
+        match comparison {
+            Comparison::Split(x) => {
+                self.draw_canvas(current, &self.get_image_rect(current.size()))?;
+                let clip = Rect::new(0.0, 0.0, x, height);
+                self.draw_canvas_clipped(reference, &self.get_image_rect(reference.size()), &clip)?;
+                self.stroke_line((x, 0.0), (x, height), COMPARISON_LINE_COLOR);
+            },
+            Comparison::SideBySide => {
+                let half = (self.size.0 / 2, self.size.1);
+                let half_width = half.0 as f64;
+                let left = self.viewport.image_rect(reference.size(), half);
+                self.draw_canvas_clipped(reference, &left, &Rect::new(0.0, 0.0, half_width, height))?;
+                let mut right = self.viewport.image_rect(current.size(), half);
+                right.x += half_width;
+                self.draw_canvas_clipped(current, &right, &Rect::new(half_width, 0.0, width - half_width, height))?;
+                self.stroke_line((half_width, 0.0), (half_width, height), COMPARISON_LINE_COLOR);
+            },
+            Comparison::Reference => self.draw_canvas_in_view(reference)?,
+        }
+        Ok(())
+    }
+
+    /// Draws the buffer through an offscreen canvas scaled by `drawImage`, without encoding it.
+    pub fn draw_image_data_fit_canvas(&self, image_data: &ImageDataWrapper) -> Result<()> {
+        self.draw_canvas_in_view(&Canvas::new_from_image_data(image_data)?)
     }
 }
@@ -0,0 +1,43 @@
+use anyhow::Result;
+use web_sys::HtmlSelectElement;
+use crate::browser;
+use super::DisplayElement;
+
+#[derive(Debug, Clone)]
+pub struct Select {
+    element: HtmlSelectElement,
+}
+
+impl Select {
+    pub fn new(element: HtmlSelectElement) -> Self {
+        Self {
+            element,
+        }
+    }
+
+    pub fn new_from_id(id: &str) -> Result<Self> {
+        let element = browser::select(id)?;
+        Ok(Self::new(element))
+    }
+
+    pub fn value(&self) -> String {
+        self.element.value()
+    }
+
+    pub fn add_event_listener_with_callback(
+        &self,
+        closure: &browser::EventClosure,
+    ) -> Result<()> {
+        browser::add_event_listener_with_callback_select(
+            &self.element,
+            "change",
+            closure,
+        )
+    }
+}
+
+impl DisplayElement for Select {
+    fn set_disabled(&self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+}
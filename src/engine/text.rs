@@ -0,0 +1,26 @@
+use anyhow::Result;
+use web_sys::HtmlElement;
+use crate::browser;
+
+/// Element showing a message to the user.
+#[derive(Debug, Clone)]
+pub struct Text {
+    element: HtmlElement,
+}
+
+impl Text {
+    pub fn new(element: HtmlElement) -> Self {
+        Self {
+            element,
+        }
+    }
+
+    pub fn new_from_id(id: &str) -> Result<Self> {
+        let element = browser::html_element(id)?;
+        Ok(Self::new(element))
+    }
+
+    pub fn set_text(&self, text: &str) {
+        self.element.set_inner_text(text);
+    }
+}
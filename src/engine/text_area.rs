@@ -0,0 +1,36 @@
+use anyhow::Result;
+use web_sys::HtmlTextAreaElement;
+use crate::browser;
+use super::DisplayElement;
+
+#[derive(Debug, Clone)]
+pub struct TextArea {
+    element: HtmlTextAreaElement,
+}
+
+impl TextArea {
+    pub fn new(element: HtmlTextAreaElement) -> Self {
+        Self {
+            element,
+        }
+    }
+
+    pub fn new_from_id(id: &str) -> Result<Self> {
+        let element = browser::text_area(id)?;
+        Ok(Self::new(element))
+    }
+
+    pub fn value(&self) -> String {
+        self.element.value()
+    }
+
+    pub fn set_value(&self, value: &str) {
+        self.element.set_value(value);
+    }
+}
+
+impl DisplayElement for TextArea {
+    fn set_disabled(&self, disabled: bool) {
+        self.element.set_disabled(disabled);
+    }
+}
@@ -0,0 +1,184 @@
+use super::Rect;
+use super::image::calculate_fitted_size;
+
+/// Zoom levels stepped through by the zoom in and out buttons.
+const ZOOM_LEVELS: [f64; 14] = [
+    0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 2.0, 3.0, 4.0, 6.0, 8.0, 12.0, 16.0, 32.0,
+];
+pub const MIN_ZOOM: f64 = 0.05;
+pub const MAX_ZOOM: f64 = 32.0;
+/// From this zoom on the pixel boundaries are drawn as a grid.
+pub const PIXEL_GRID_ZOOM: f64 = 8.0;
+
+/// Which part of the image is shown on the canvas and how large.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Viewport {
+    /// Canvas pixels per image pixel, or `None` to fit the image into the canvas.
+    zoom: Option<f64>,
+    /// Offset of the image center from the canvas center in canvas pixels.
+    pan: (f64, f64),
+}
+
+impl Viewport {
+    pub fn zoom(&self) -> Option<f64> {
+        self.zoom
+    }
+
+    /// Canvas pixels per image pixel. Fitting never upscales the image.
+    pub fn scale(&self, image_size: (u32, u32), canvas_size: (u32, u32)) -> f64 {
+        if let Some(zoom) = self.zoom {
+            return zoom;
+        }
+        if image_size.0 == 0 {
+            return 1.0;
+        }
+        let (width, _) = calculate_fitted_size(image_size, canvas_size.0 as f64, canvas_size.1 as f64);
+        width / image_size.0 as f64
+    }
+
+    /// Rectangle the image covers on the canvas, which may extend past its edges.
+    pub fn image_rect(&self, image_size: (u32, u32), canvas_size: (u32, u32)) -> Rect {
+        let scale = self.scale(image_size, canvas_size);
+        let width = image_size.0 as f64 * scale;
+        let height = image_size.1 as f64 * scale;
+        Rect::new(
+            (canvas_size.0 as f64 - width) / 2.0 + self.pan.0,
+            (canvas_size.1 as f64 - height) / 2.0 + self.pan.1,
+            width,
+            height,
+        )
+    }
+
+    /// Sets the zoom around the canvas center, or fits and centers the image for `None`.
+    pub fn set_zoom(&mut self, zoom: Option<f64>) {
+        match zoom {
+            Some(zoom) => {
+                let ratio = zoom.clamp(MIN_ZOOM, MAX_ZOOM) / self.zoom.unwrap_or(zoom);
+                self.pan = (self.pan.0 * ratio, self.pan.1 * ratio);
+                self.zoom = Some(zoom.clamp(MIN_ZOOM, MAX_ZOOM));
+            },
+            None => *self = Self::default(),
+        }
+    }
+
+    /// Sets the zoom keeping the image point under `anchor` (canvas pixels) in place.
+    pub fn zoom_at(&mut self, image_size: (u32, u32), canvas_size: (u32, u32),
+                   zoom: f64, anchor: (f64, f64)) {
+        let rect = self.image_rect(image_size, canvas_size);
+        let old = self.scale(image_size, canvas_size);
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+        let ratio = zoom / old;
+        let x = anchor.0 - (anchor.0 - rect.x) * ratio;
+        let y = anchor.1 - (anchor.1 - rect.y) * ratio;
+        self.zoom = Some(zoom);
+        self.pan = (
+            x + image_size.0 as f64 * zoom / 2.0 - canvas_size.0 as f64 / 2.0,
+            y + image_size.1 as f64 * zoom / 2.0 - canvas_size.1 as f64 / 2.0,
+        );
+        self.clamp_pan(image_size, canvas_size);
+    }
+
+    /// Moves the image by `delta` canvas pixels.
+    pub fn pan_by(&mut self, image_size: (u32, u32), canvas_size: (u32, u32), delta: (f64, f64)) {
+        self.zoom = Some(self.scale(image_size, canvas_size));
+        self.pan = (self.pan.0 + delta.0, self.pan.1 + delta.1);
+        self.clamp_pan(image_size, canvas_size);
+    }
+
+    /// Keeps the image from being moved further than until its edge meets the canvas center.
+    fn clamp_pan(&mut self, image_size: (u32, u32), canvas_size: (u32, u32)) {
+        let rect = self.image_rect(image_size, canvas_size);
+        let limit_x = rect.width.max(canvas_size.0 as f64) / 2.0;
+        let limit_y = rect.height.max(canvas_size.1 as f64) / 2.0;
+        self.pan = (self.pan.0.clamp(-limit_x, limit_x), self.pan.1.clamp(-limit_y, limit_y));
+    }
+}
+
+/// The next zoom level above or below `scale`.
+pub fn step_zoom(scale: f64, zoom_in: bool) -> f64 {
+    let level = if zoom_in {
+        ZOOM_LEVELS.iter().copied().find(|&level| level > scale * 1.001)
+    } else {
+        ZOOM_LEVELS.iter().rev().copied().find(|&level| level < scale * 0.999)
+    };
+    level.unwrap_or(scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IMAGE: (u32, u32) = (400, 300);
+    const CANVAS: (u32, u32) = (200, 100);
+
+    /// Image point shown at `canvas_point`.
+    fn image_point(viewport: &Viewport, canvas_point: (f64, f64)) -> (f64, f64) {
+        let rect = viewport.image_rect(IMAGE, CANVAS);
+        let scale = viewport.scale(IMAGE, CANVAS);
+        ((canvas_point.0 - rect.x) / scale, (canvas_point.1 - rect.y) / scale)
+    }
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!((a.0 - b.0).abs() < 1e-9 && (a.1 - b.1).abs() < 1e-9, "{:?} is not {:?}", a, b);
+    }
+
+    #[test]
+    fn fitting_never_upscales() {
+        let viewport = Viewport::default();
+        assert!((viewport.scale(IMAGE, CANVAS) - 1.0 / 3.0).abs() < 1e-9);
+        assert_eq!(viewport.scale((20, 10), CANVAS), 1.0);
+    }
+
+    #[test]
+    fn zoom_at_keeps_the_point_under_the_anchor() {
+        let mut viewport = Viewport::default();
+        for (zoom, anchor) in [(2.0, (50.0, 30.0)), (4.0, (150.0, 80.0)), (1.0, (100.0, 50.0)), (0.5, (10.0, 90.0))] {
+            let before = image_point(&viewport, anchor);
+            viewport.zoom_at(IMAGE, CANVAS, zoom, anchor);
+            assert_eq!(viewport.zoom(), Some(zoom));
+            assert_close(image_point(&viewport, anchor), before);
+        }
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut viewport = Viewport::default();
+        viewport.zoom_at(IMAGE, CANVAS, 1000.0, (0.0, 0.0));
+        assert_eq!(viewport.zoom(), Some(MAX_ZOOM));
+        viewport.set_zoom(Some(0.0));
+        assert_eq!(viewport.zoom(), Some(MIN_ZOOM));
+        viewport.set_zoom(None);
+        assert_eq!(viewport, Viewport::default());
+    }
+
+    #[test]
+    fn pan_stops_where_the_image_edge_meets_the_canvas_center() {
+        let mut viewport = Viewport::default();
+        viewport.set_zoom(Some(1.0));
+        viewport.pan_by(IMAGE, CANVAS, (10.0, -5.0));
+        assert_eq!(viewport.pan, (10.0, -5.0));
+        viewport.pan_by(IMAGE, CANVAS, (1e6, 1e6));
+        let rect = viewport.image_rect(IMAGE, CANVAS);
+        assert_close((rect.x, rect.y), (CANVAS.0 as f64 / 2.0, CANVAS.1 as f64 / 2.0));
+        viewport.pan_by(IMAGE, CANVAS, (-1e6, -1e6));
+        let rect = viewport.image_rect(IMAGE, CANVAS);
+        assert_close((rect.x + rect.width, rect.y + rect.height), (CANVAS.0 as f64 / 2.0, CANVAS.1 as f64 / 2.0));
+    }
+
+    #[test]
+    fn small_image_pans_within_the_canvas() {
+        let mut viewport = Viewport::default();
+        viewport.pan_by((20, 10), CANVAS, (1e6, 0.0));
+        assert_eq!(viewport.pan, (CANVAS.0 as f64 / 2.0, 0.0));
+    }
+
+    #[test]
+    fn step_zoom_moves_to_the_next_level() {
+        assert_eq!(step_zoom(1.0, true), 2.0);
+        assert_eq!(step_zoom(1.0, false), 0.75);
+        assert_eq!(step_zoom(1.5, true), 2.0);
+        assert_eq!(step_zoom(1.5, false), 1.0);
+        assert_eq!(step_zoom(MAX_ZOOM, true), MAX_ZOOM);
+        assert_eq!(step_zoom(MIN_ZOOM, false), MIN_ZOOM);
+    }
+}
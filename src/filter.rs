@@ -0,0 +1,142 @@
+use std::ops::Range;
+use anyhow::{Result, anyhow};
+
+mod color;
+mod histogram;
+mod white_balance;
+mod point;
+mod edge_mode;
+mod blur;
+mod sharpen;
+mod region;
+mod convolution;
+mod edge_detection;
+mod denoise;
+mod morphology;
+mod crop;
+mod orientation;
+mod resample;
+mod rotate;
+mod resize;
+mod canvas_size;
+mod trim;
+mod perspective;
+mod deskew;
+
+pub use color::{parse_hex_color, format_hex_color, rgb_to_hsl, luminance};
+pub use histogram::{HistogramEqualization, Clahe};
+pub use white_balance::WhiteBalance;
+pub use point::{PointOperation, PointFilter};
+pub use edge_mode::EdgeMode;
+pub use blur::{Blur, BlurKind};
+pub use sharpen::UnsharpMask;
+pub use region::copy_region;
+pub use convolution::{
+    Kernel, KernelPreset, Convolution, ConvolutionOptions, ConvolutionChannels,
+};
+pub use edge_detection::{
+    GradientOperator, Gradient, LaplacianOfGaussian, Canny,
+};
+pub use denoise::{Median, Bilateral, NonLocalMeans};
+pub use morphology::{
+    StructuringElement, MorphologyOperation, Morphology, RemoveSpecks,
+};
+pub use crop::Crop;
+pub use orientation::{Orientation, Reorient};
+pub use resample::Interpolation;
+pub use rotate::{Rotate, RotateFit};
+pub use resize::{Resize, ResizeFilter};
+pub use canvas_size::{CanvasAnchor, CanvasSize};
+pub use trim::{TrimMode, TrimmedSides, Trim};
+pub use perspective::{Perspective, order_corners, estimate_rectangle_size};
+pub use deskew::Deskew;
+
+const PIXELS_PER_STEP: u32 = 250000;
+/// Longest side of an image a filter may produce.
+pub const MAX_IMAGE_SIDE: u32 = 16384;
+/// Pixel count of an image a filter may produce, well below what exhausts the wasm memory.
+pub const MAX_IMAGE_PIXELS: u64 = 40_000_000;
+
+/// An operation on an RGBA buffer that runs a few rows at a time.
+pub trait Filter {
+    /// Width and height of the image produced by this filter.
+    fn output_size(&self) -> (u32, u32);
+
+    /// Processes the next chunk of `data` and returns `true` once finished.
+    /// `data` starts as a copy of the source image when the size is unchanged.
+    /// A filter that measures the image first may change its output size between steps,
+    /// and `data` is then replaced by a zeroed buffer of the new size.
+    fn step(&mut self, data: &mut [u8]) -> bool;
+}
+
+/// Fails for sizes over [`MAX_IMAGE_SIDE`] or [`MAX_IMAGE_PIXELS`], so a huge target is reported
+/// instead of aborting on the allocation.
+pub fn check_image_size(width: u32, height: u32) -> Result<()> {
+    if width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE || width as u64 * height as u64 > MAX_IMAGE_PIXELS {
+        return Err(anyhow!("Image size {}x{} is over the limit of {} pixels per side and {} pixels",
+            width, height, MAX_IMAGE_SIDE, MAX_IMAGE_PIXELS));
+    }
+    Ok(())
+}
+
+/// Byte range of `rows` in an RGBA buffer of the given width.
+pub fn row_bytes(rows: &Range<u32>, width: u32) -> Range<usize> {
+    let row_len = width as usize * 4;
+    rows.start as usize * row_len..rows.end as usize * row_len
+}
+
+#[derive(Debug, Clone)]
+pub struct RowCursor {
+    next: u32,
+    height: u32,
+    rows_per_step: u32,
+}
+
+impl RowCursor {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            next: 0,
+            height,
+            rows_per_step: (PIXELS_PER_STEP / width.max(1)).max(1),
+        }
+    }
+
+    /// Cursor for filters doing about `cost` times the work of a point operation per pixel.
+    pub fn with_cost(width: u32, height: u32, cost: u32) -> Self {
+        Self {
+            next: 0,
+            height,
+            rows_per_step: (PIXELS_PER_STEP / cost.max(1) / width.max(1)).max(1),
+        }
+    }
+
+    pub fn next_rows(&mut self) -> Option<Range<u32>> {
+        if self.is_done() {
+            return None;
+        }
+        let start = self.next;
+        self.next = (start + self.rows_per_step).min(self.height);
+        Some(start..self.next)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.next >= self.height
+    }
+}
+
+/// Runs `filter` on an image of `size` until it is finished, like the editor does.
+#[cfg(test)]
+fn run_to_end(filter: &mut dyn Filter, data: &[u8], size: (u32, u32)) -> Vec<u8> {
+    let output_len = |(width, height): (u32, u32)| width as usize * height as usize * 4;
+    let mut output = if filter.output_size() == size {
+        data.to_vec()
+    } else {
+        vec![0; output_len(filter.output_size())]
+    };
+    while !filter.step(&mut output) {
+        if output.len() != output_len(filter.output_size()) {
+            output = vec![0; output_len(filter.output_size())];
+        }
+    }
+    output
+}
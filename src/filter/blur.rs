@@ -0,0 +1,194 @@
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode, row_bytes};
+use super::color::clamp_u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlurKind {
+    Gaussian,
+    Box,
+}
+
+impl FromStr for BlurKind {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "gaussian" => Ok(BlurKind::Gaussian),
+            "box" => Ok(BlurKind::Box),
+            _ => Err(anyhow!("Unknown blur {:#?}", value)),
+        }
+    }
+}
+
+/// Largest blur radius, also used by the filters built on the Gaussian blur.
+const MAX_RADIUS: u32 = 100;
+
+fn gaussian_weights(radius: u32) -> Vec<f32> {
+    let sigma = (radius as f64 / 3.0).max(0.5);
+    let weights: Vec<f64> = (-(radius as i64)..=radius as i64)
+        .map(|offset| (-((offset * offset) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f64 = weights.iter().sum();
+    weights.iter().map(|weight| (weight / total) as f32).collect()
+}
+
+fn premultiply(pixel: &[u8]) -> [f32; 4] {
+    let alpha = pixel[3] as f32;
+    [
+        pixel[0] as f32 * alpha / 255.0,
+        pixel[1] as f32 * alpha / 255.0,
+        pixel[2] as f32 * alpha / 255.0,
+        alpha,
+    ]
+}
+
+fn add_scaled(sum: &mut [f32], values: &[f32], scale: f32) {
+    for (sum, value) in sum.iter_mut().zip(values) {
+        *sum += value * scale;
+    }
+}
+
+/// Separable Gaussian or running-sum box blur of premultiplied RGBA.
+/// The horizontal pass goes to an intermediate buffer before the vertical pass.
+#[derive(Debug, Clone)]
+pub struct Blur {
+    weights: Option<Vec<f32>>,
+    radius: u32,
+    edge_mode: EdgeMode,
+    source: Vec<u8>,
+    temp: Vec<f32>,
+    column_sum: Vec<f32>,
+    size: (u32, u32),
+    horizontal: RowCursor,
+    vertical: RowCursor,
+}
+
+impl Blur {
+    pub fn new(kind: BlurKind, data: &[u8], width: u32, height: u32,
+               radius: u32, edge_mode: EdgeMode) -> Result<Self> {
+        if radius == 0 {
+            return Err(anyhow!("Blur radius must be at least 1"));
+        }
+        if radius > MAX_RADIUS {
+            return Err(anyhow!("Blur radius must be at most {}: {}", MAX_RADIUS, radius));
+        }
+        let (weights, cost) = match kind {
+            BlurKind::Gaussian => (Some(gaussian_weights(radius)), 2 * radius + 1),
+            BlurKind::Box => (None, 1),
+        };
+        Ok(Self {
+            weights,
+            radius,
+            edge_mode,
+            source: data.to_vec(),
+            temp: vec![0.0; data.len()],
+            column_sum: Vec::new(),
+            size: (width, height),
+            horizontal: RowCursor::with_cost(width, height, cost),
+            vertical: RowCursor::with_cost(width, height, cost),
+        })
+    }
+
+    fn horizontal_row(&mut self, y: u32) {
+        let width = self.size.0;
+        let radius = self.radius as i64;
+        let row: Vec<f32> = self.source[row_bytes(&(y..y + 1), width)]
+            .chunks_exact(4)
+            .flat_map(premultiply)
+            .collect();
+        let sample = |x: i64| {
+            let index = self.edge_mode.index(x, width) as usize * 4;
+            &row[index..index + 4]
+        };
+        let output = &mut self.temp[row_bytes(&(y..y + 1), width)];
+        match &self.weights {
+            Some(weights) => {
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    pixel.fill(0.0);
+                    for (k, weight) in weights.iter().enumerate() {
+                        add_scaled(pixel, sample(x as i64 + k as i64 - radius), *weight);
+                    }
+                }
+            },
+            None => {
+                let scale = 1.0 / (2 * radius + 1) as f32;
+                let mut sum = [0.0; 4];
+                for x in -radius..=radius {
+                    add_scaled(&mut sum, sample(x), 1.0);
+                }
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    for (value, sum) in pixel.iter_mut().zip(sum) {
+                        *value = sum * scale;
+                    }
+                    add_scaled(&mut sum, sample(x as i64 + radius + 1), 1.0);
+                    add_scaled(&mut sum, sample(x as i64 - radius), -1.0);
+                }
+            },
+        }
+    }
+
+    fn vertical_row(&mut self, y: u32, data: &mut [u8]) {
+        let (width, height) = self.size;
+        let radius = self.radius as i64;
+        let (edge_mode, temp) = (self.edge_mode, &self.temp);
+        let temp_row = |y: i64| {
+            let y = edge_mode.index(y, height);
+            &temp[row_bytes(&(y..y + 1), width)]
+        };
+        let y_position = y as i64;
+        let mut sum = vec![0.0; width as usize * 4];
+        match &self.weights {
+            Some(weights) => {
+                for (k, weight) in weights.iter().enumerate() {
+                    add_scaled(&mut sum, temp_row(y_position + k as i64 - radius), *weight);
+                }
+            },
+            None => {
+                let column_sum = &mut self.column_sum;
+                if y == 0 {
+                    *column_sum = vec![0.0; width as usize * 4];
+                    for row in -radius..=radius {
+                        add_scaled(column_sum, temp_row(row), 1.0);
+                    }
+                }
+                add_scaled(&mut sum, column_sum, 1.0 / (2 * radius + 1) as f32);
+                add_scaled(column_sum, temp_row(y_position + radius + 1), 1.0);
+                add_scaled(column_sum, temp_row(y_position - radius), -1.0);
+            },
+        }
+        let output = &mut data[row_bytes(&(y..y + 1), width)];
+        for (pixel, sum) in output.chunks_exact_mut(4).zip(sum.chunks_exact(4)) {
+            let alpha = sum[3];
+            for (value, sum) in pixel.iter_mut().zip(sum).take(3) {
+                *value = if alpha > 0.0 {
+                    clamp_u8((sum * 255.0 / alpha) as f64)
+                } else {
+                    0
+                };
+            }
+            pixel[3] = clamp_u8(alpha as f64);
+        }
+    }
+}
+
+impl Filter for Blur {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.horizontal.next_rows() {
+            for y in rows {
+                self.horizontal_row(y);
+            }
+            return false;
+        }
+        if let Some(rows) = self.vertical.next_rows() {
+            for y in rows {
+                self.vertical_row(y, data);
+            }
+        }
+        self.vertical.is_done()
+    }
+}
@@ -0,0 +1,182 @@
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, row_bytes, check_image_size};
+
+/// Where the image is placed when the canvas grows or shrinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanvasAnchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl CanvasAnchor {
+    /// Horizontal and vertical position as a fraction of the size difference.
+    fn fractions(self) -> (f64, f64) {
+        match self {
+            CanvasAnchor::TopLeft => (0.0, 0.0),
+            CanvasAnchor::Top => (0.5, 0.0),
+            CanvasAnchor::TopRight => (1.0, 0.0),
+            CanvasAnchor::Left => (0.0, 0.5),
+            CanvasAnchor::Center => (0.5, 0.5),
+            CanvasAnchor::Right => (1.0, 0.5),
+            CanvasAnchor::BottomLeft => (0.0, 1.0),
+            CanvasAnchor::Bottom => (0.5, 1.0),
+            CanvasAnchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+impl FromStr for CanvasAnchor {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "top_left" => Ok(CanvasAnchor::TopLeft),
+            "top" => Ok(CanvasAnchor::Top),
+            "top_right" => Ok(CanvasAnchor::TopRight),
+            "left" => Ok(CanvasAnchor::Left),
+            "center" => Ok(CanvasAnchor::Center),
+            "right" => Ok(CanvasAnchor::Right),
+            "bottom_left" => Ok(CanvasAnchor::BottomLeft),
+            "bottom" => Ok(CanvasAnchor::Bottom),
+            "bottom_right" => Ok(CanvasAnchor::BottomRight),
+            _ => Err(anyhow!("Unknown anchor {:#?}", value)),
+        }
+    }
+}
+
+/// Places the image on a canvas of another size, filling the uncovered area.
+#[derive(Debug, Clone)]
+pub struct CanvasSize {
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    /// Position of the source image on the new canvas, negative when it is cut off.
+    offset: (i64, i64),
+    fill: [u8; 4],
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl CanvasSize {
+    pub fn new(data: &[u8], width: u32, height: u32, canvas_width: u32, canvas_height: u32,
+               anchor: CanvasAnchor, fill: [u8; 4]) -> Result<Self> {
+        if canvas_width == 0 || canvas_height == 0 {
+            return Err(anyhow!("Canvas size must not be empty"));
+        }
+        check_image_size(canvas_width, canvas_height)?;
+        let (fraction_x, fraction_y) = anchor.fractions();
+        let offset = (
+            ((canvas_width as f64 - width as f64) * fraction_x).round() as i64,
+            ((canvas_height as f64 - height as f64) * fraction_y).round() as i64,
+        );
+        Ok(Self {
+            source: data.to_vec(),
+            source_size: (width, height),
+            offset,
+            fill,
+            size: (canvas_width, canvas_height),
+            cursor: RowCursor::new(canvas_width, canvas_height),
+        })
+    }
+
+    /// Surrounds the image with a border of `border_width` pixels.
+    pub fn border(data: &[u8], width: u32, height: u32, border_width: u32, color: [u8; 4]) -> Result<Self> {
+        let grow = |length: u32| border_width.checked_mul(2)
+            .and_then(|border| length.checked_add(border))
+            .ok_or_else(|| anyhow!("Border of {} pixels is too wide", border_width));
+        Self::new(data, width, height, grow(width)?, grow(height)?, CanvasAnchor::Center, color)
+    }
+}
+
+impl Filter for CanvasSize {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let width = self.size.0;
+        let (source_width, source_height) = (self.source_size.0 as i64, self.source_size.1 as i64);
+        let (offset_x, offset_y) = self.offset;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                let output = &mut data[row_bytes(&(y..y + 1), width)];
+                let sy = y as i64 - offset_y;
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    let sx = x as i64 - offset_x;
+                    if sx < 0 || sy < 0 || sx >= source_width || sy >= source_height {
+                        pixel.copy_from_slice(&self.fill);
+                    } else {
+                        let index = (sy * source_width + sx) as usize * 4;
+                        pixel.copy_from_slice(&self.source[index..index + 4]);
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::{MAX_IMAGE_SIDE, run_to_end};
+
+    fn offset(anchor: CanvasAnchor, canvas_size: (u32, u32)) -> (i64, i64) {
+        CanvasSize::new(&[0; 2 * 2 * 4], 2, 2, canvas_size.0, canvas_size.1, anchor, [0; 4])
+            .unwrap()
+            .offset
+    }
+
+    #[test]
+    fn anchor_offsets_when_growing() {
+        let offsets = [
+            (CanvasAnchor::TopLeft, (0, 0)),
+            (CanvasAnchor::Top, (1, 0)),
+            (CanvasAnchor::TopRight, (2, 0)),
+            (CanvasAnchor::Left, (0, 2)),
+            (CanvasAnchor::Center, (1, 2)),
+            (CanvasAnchor::Right, (2, 2)),
+            (CanvasAnchor::BottomLeft, (0, 4)),
+            (CanvasAnchor::Bottom, (1, 4)),
+            (CanvasAnchor::BottomRight, (2, 4)),
+        ];
+        for (anchor, expected) in offsets {
+            assert_eq!(offset(anchor, (4, 6)), expected, "{:?}", anchor);
+        }
+    }
+
+    #[test]
+    fn anchor_offsets_when_shrinking() {
+        assert_eq!(offset(CanvasAnchor::TopLeft, (1, 1)), (0, 0));
+        assert_eq!(offset(CanvasAnchor::Center, (1, 1)), (-1, -1));
+        assert_eq!(offset(CanvasAnchor::BottomRight, (1, 1)), (-1, -1));
+    }
+
+    #[test]
+    fn border_surrounds_the_image() {
+        let data = [9; 4];
+        let mut border = CanvasSize::border(&data, 1, 1, 1, [1, 2, 3, 255]).unwrap();
+        assert_eq!(border.output_size(), (3, 3));
+        let output = run_to_end(&mut border, &data, (1, 1));
+        for (index, pixel) in output.chunks_exact(4).enumerate() {
+            let expected = if index == 4 { [9; 4] } else { [1, 2, 3, 255] };
+            assert_eq!(pixel, expected);
+        }
+    }
+
+    #[test]
+    fn sizes_over_the_limit_are_errors() {
+        let data = [0; 4];
+        assert!(CanvasSize::new(&data, 1, 1, MAX_IMAGE_SIDE + 1, 1, CanvasAnchor::Center, [0; 4]).is_err());
+        assert!(CanvasSize::new(&data, 1, 1, 10000, 10000, CanvasAnchor::Center, [0; 4]).is_err());
+        assert!(CanvasSize::border(&data, 1, 1, u32::MAX / 2, [0; 4]).is_err());
+        assert!(CanvasSize::border(&data, 1, 1, u32::MAX, [0; 4]).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{Result, anyhow};
+
+pub fn clamp_u8(value: f64) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+pub fn luminance(r: u8, g: u8, b: u8) -> f64 {
+    0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64
+}
+
+/// Moves the luminance of a pixel to `target` while keeping its chroma.
+pub fn set_luminance(pixel: &mut [u8], target: f64) {
+    let delta = target - luminance(pixel[0], pixel[1], pixel[2]);
+    for channel in pixel.iter_mut().take(3) {
+        *channel = clamp_u8(*channel as f64 + delta);
+    }
+}
+
+/// Converts hue in degrees, saturation and value in `0..=1` to RGB.
+pub fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> [u8; 3] {
+    let chroma = value * saturation;
+    let sector = (hue.rem_euclid(360.0)) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    [clamp_u8((r + m) * 255.0), clamp_u8((g + m) * 255.0), clamp_u8((b + m) * 255.0)]
+}
+
+/// Converts RGB to hue in degrees, saturation and lightness in `0..=1`.
+pub fn rgb_to_hsl(r: u8, g: u8, b: u8) -> (f64, f64, f64) {
+    let (r, g, b) = (r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        (b - r) / delta + 2.0
+    } else {
+        (r - g) / delta + 4.0
+    };
+    (hue * 60.0, saturation, lightness)
+}
+
+/// Formats a color as `#rrggbb` for `<input type="color">`.
+pub fn format_hex_color(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+/// Parses a `#rrggbb` color as produced by `<input type="color">`.
+pub fn parse_hex_color(value: &str) -> Result<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(anyhow!("Invalid color {:#?}", value));
+    }
+    let mut color = [0; 3];
+    for (channel, entry) in color.iter_mut().enumerate() {
+        *entry = u8::from_str_radix(&hex[channel * 2..channel * 2 + 2], 16)
+            .map_err(|err| anyhow!("Invalid color {:#?}: {:#?}", value, err))?;
+    }
+    Ok(color)
+}
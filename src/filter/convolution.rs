@@ -0,0 +1,303 @@
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode};
+use super::color::{clamp_u8, luminance, set_luminance};
+
+/// Weights of a convolution, anchored at the center cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Kernel {
+    width: u32,
+    height: u32,
+    values: Vec<f64>,
+}
+
+impl Kernel {
+    pub fn new(width: u32, height: u32, values: Vec<f64>) -> Result<Self> {
+        if width == 0 || height == 0 {
+            return Err(anyhow!("Kernel must not be empty"));
+        }
+        if values.len() != (width * height) as usize {
+            return Err(anyhow!("Kernel {}x{} needs {} values, got {}",
+                width, height, width * height, values.len()));
+        }
+        Ok(Self { width, height, values })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.values.iter().sum()
+    }
+
+    fn get(&self, x: u32, y: u32) -> f64 {
+        self.values[(y * self.width + x) as usize]
+    }
+}
+
+/// Parses one row per line, with values separated by spaces or commas.
+impl FromStr for Kernel {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut rows = Vec::new();
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let row = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|value| !value.is_empty())
+                .map(|value| value.parse::<f64>()
+                    .map_err(|err| anyhow!("Invalid kernel value {:#?}: {}", value, err)))
+                .collect::<Result<Vec<_>>>()?;
+            rows.push(row);
+        }
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            return Err(anyhow!("Kernel rows must have the same length"));
+        }
+        Kernel::new(width as u32, rows.len() as u32, rows.concat())
+    }
+}
+
+impl std::fmt::Display for Kernel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.values.chunks(self.width as usize) {
+            let row: Vec<String> = row.iter().map(f64::to_string).collect();
+            writeln!(f, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConvolutionChannels {
+    Rgb,
+    Luminance,
+}
+
+impl FromStr for ConvolutionChannels {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "rgb" => Ok(ConvolutionChannels::Rgb),
+            "luminance" => Ok(ConvolutionChannels::Luminance),
+            _ => Err(anyhow!("Unknown channels {:#?}", value)),
+        }
+    }
+}
+
+/// `divisor` of 0 divides by the kernel sum, or by 1 when the kernel sums to 0.
+#[derive(Debug, Clone, Copy)]
+pub struct ConvolutionOptions {
+    pub divisor: f64,
+    pub bias: f64,
+    pub channels: ConvolutionChannels,
+    pub edge_mode: EdgeMode,
+}
+
+impl Default for ConvolutionOptions {
+    fn default() -> Self {
+        Self {
+            divisor: 1.0,
+            bias: 0.0,
+            channels: ConvolutionChannels::Rgb,
+            edge_mode: EdgeMode::Clamp,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KernelPreset {
+    Sharpen,
+    Emboss,
+    Outline,
+    MotionBlur,
+}
+
+impl KernelPreset {
+    pub fn kernel(self) -> Kernel {
+        let (size, values) = match self {
+            KernelPreset::Sharpen => (3, vec![
+                0.0, -1.0, 0.0,
+                -1.0, 5.0, -1.0,
+                0.0, -1.0, 0.0,
+            ]),
+            KernelPreset::Emboss => (3, vec![
+                -2.0, -1.0, 0.0,
+                -1.0, 1.0, 1.0,
+                0.0, 1.0, 2.0,
+            ]),
+            KernelPreset::Outline => (3, vec![
+                -1.0, -1.0, -1.0,
+                -1.0, 8.0, -1.0,
+                -1.0, -1.0, -1.0,
+            ]),
+            KernelPreset::MotionBlur => (9, (0..81)
+                .map(|index| if index % 10 == 0 { 1.0 } else { 0.0 })
+                .collect()),
+        };
+        Kernel { width: size, height: size, values }
+    }
+
+    pub fn options(self) -> ConvolutionOptions {
+        match self {
+            KernelPreset::MotionBlur => ConvolutionOptions {
+                divisor: 0.0,
+                ..ConvolutionOptions::default()
+            },
+            _ => ConvolutionOptions::default(),
+        }
+    }
+}
+
+impl FromStr for KernelPreset {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sharpen" => Ok(KernelPreset::Sharpen),
+            "emboss" => Ok(KernelPreset::Emboss),
+            "outline" => Ok(KernelPreset::Outline),
+            "motion_blur" => Ok(KernelPreset::MotionBlur),
+            _ => Err(anyhow!("Unknown kernel preset {:#?}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Convolution {
+    kernel: Kernel,
+    divisor: f64,
+    options: ConvolutionOptions,
+    source: Vec<u8>,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Convolution {
+    pub fn new(kernel: Kernel, data: &[u8], width: u32, height: u32,
+               options: ConvolutionOptions) -> Result<Self> {
+        if !options.divisor.is_finite() || !options.bias.is_finite() {
+            return Err(anyhow!("Divisor and bias must be finite numbers"));
+        }
+        let divisor = if options.divisor != 0.0 {
+            options.divisor
+        } else if kernel.sum() != 0.0 {
+            kernel.sum()
+        } else {
+            1.0
+        };
+        let source = match options.channels {
+            ConvolutionChannels::Rgb => data.to_vec(),
+            ConvolutionChannels::Luminance => data
+                .chunks_exact(4)
+                .map(|pixel| luminance(pixel[0], pixel[1], pixel[2]).round() as u8)
+                .collect(),
+        };
+        let (kernel_width, kernel_height) = kernel.size();
+        Ok(Self {
+            kernel,
+            divisor,
+            options,
+            source,
+            size: (width, height),
+            cursor: RowCursor::with_cost(width, height, kernel_width * kernel_height),
+        })
+    }
+
+    pub fn new_from_preset(preset: KernelPreset, data: &[u8], width: u32, height: u32) -> Result<Self> {
+        Self::new(preset.kernel(), data, width, height, preset.options())
+    }
+
+    /// Weighted sum of the first `N` source values of the pixels around `(x, y)`.
+    fn convolve<const N: usize>(&self, x: u32, y: u32) -> [f64; N] {
+        let (width, height) = self.size;
+        let (kernel_width, kernel_height) = self.kernel.size();
+        let edge_mode = self.options.edge_mode;
+        let stride = match self.options.channels {
+            ConvolutionChannels::Rgb => 4,
+            ConvolutionChannels::Luminance => 1,
+        };
+        let mut sum = [0.0; N];
+        for ky in 0..kernel_height {
+            let sy = edge_mode.index(y as i64 + ky as i64 - (kernel_height / 2) as i64, height);
+            for kx in 0..kernel_width {
+                let weight = self.kernel.get(kx, ky);
+                if weight == 0.0 {
+                    continue;
+                }
+                let sx = edge_mode.index(x as i64 + kx as i64 - (kernel_width / 2) as i64, width);
+                let index = (sy * width + sx) as usize * stride;
+                for (sum, value) in sum.iter_mut().zip(&self.source[index..index + N]) {
+                    *sum += *value as f64 * weight;
+                }
+            }
+        }
+        sum.map(|value| value / self.divisor + self.options.bias)
+    }
+}
+
+impl Filter for Convolution {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let width = self.size.0;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                for x in 0..width {
+                    let index = ((y * width + x) * 4) as usize;
+                    let pixel = &mut data[index..index + 4];
+                    match self.options.channels {
+                        ConvolutionChannels::Rgb => {
+                            for (value, sum) in pixel.iter_mut().zip(self.convolve::<3>(x, y)) {
+                                *value = clamp_u8(sum);
+                            }
+                        },
+                        ConvolutionChannels::Luminance => {
+                            let [target] = self.convolve::<1>(x, y);
+                            set_luminance(pixel, target.clamp(0.0, 255.0));
+                        },
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernel_from_str_reads_rows() {
+        let kernel: Kernel = "1 2 3\n4,5,6\n 7, 8  9 ".parse().unwrap();
+        assert_eq!(kernel.size(), (3, 3));
+        assert_eq!(kernel.get(0, 0), 1.0);
+        assert_eq!(kernel.get(2, 1), 6.0);
+        assert_eq!(kernel.get(1, 2), 8.0);
+        assert_eq!(kernel.sum(), 45.0);
+    }
+
+    #[test]
+    fn kernel_from_str_skips_blank_lines() {
+        let kernel: Kernel = "\n-1 0.5\n\n2 -0.25\n\n".parse().unwrap();
+        assert_eq!(kernel, Kernel::new(2, 2, vec![-1.0, 0.5, 2.0, -0.25]).unwrap());
+    }
+
+    #[test]
+    fn kernel_from_str_rejects_invalid_text() {
+        assert!("".parse::<Kernel>().is_err());
+        assert!("1 2\n3".parse::<Kernel>().is_err());
+        assert!("1 x 3".parse::<Kernel>().is_err());
+    }
+
+    #[test]
+    fn kernel_round_trips_through_display() {
+        let kernel = KernelPreset::Emboss.kernel();
+        assert_eq!(kernel.to_string().parse::<Kernel>().unwrap(), kernel);
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, row_bytes};
+
+/// Copies a rectangle out of the image.
+#[derive(Debug, Clone)]
+pub struct Crop {
+    source: Vec<u8>,
+    source_width: u32,
+    origin: (u32, u32),
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Crop {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               x: u32, y: u32, crop_width: u32, crop_height: u32) -> Result<Self> {
+        if crop_width == 0 || crop_height == 0 {
+            return Err(anyhow!("Crop area must not be empty"));
+        }
+        if x + crop_width > width || y + crop_height > height {
+            return Err(anyhow!("Crop area {}x{} at ({}, {}) is outside of the {}x{} image",
+                crop_width, crop_height, x, y, width, height));
+        }
+        Ok(Self {
+            source: data.to_vec(),
+            source_width: width,
+            origin: (x, y),
+            size: (crop_width, crop_height),
+            cursor: RowCursor::new(crop_width, crop_height),
+        })
+    }
+}
+
+impl Filter for Crop {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (x, y) = self.origin;
+        let width = self.size.0 as usize * 4;
+        if let Some(rows) = self.cursor.next_rows() {
+            for row in rows {
+                let start = ((y + row) * self.source_width + x) as usize * 4;
+                data[row_bytes(&(row..row + 1), self.size.0)]
+                    .copy_from_slice(&self.source[start..start + width]);
+            }
+        }
+        self.cursor.is_done()
+    }
+}
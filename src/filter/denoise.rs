@@ -0,0 +1,297 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode};
+use super::color::{clamp_u8, luminance};
+
+/// Largest median and bilateral radius.
+const MAX_RADIUS: u32 = 20;
+/// Largest non-local means search radius, which costs nine comparisons per window pixel.
+const MAX_SEARCH_RADIUS: u32 = 15;
+
+fn pixel_index(x: u32, y: u32, width: u32) -> usize {
+    (y * width + x) as usize * 4
+}
+
+/// Side of the square window of `radius`.
+fn window_side(radius: u32) -> Result<u32> {
+    radius.checked_mul(2)
+        .and_then(|diameter| diameter.checked_add(1))
+        .ok_or_else(|| anyhow!("Window of radius {} is too large", radius))
+}
+
+/// Number of pixels in the square window of `radius`, times `scale`.
+fn window_cost(radius: u32, scale: u32) -> Result<u32> {
+    let side = window_side(radius)?;
+    side.checked_mul(side)
+        .and_then(|area| area.checked_mul(scale))
+        .ok_or_else(|| anyhow!("Window of radius {} is too large", radius))
+}
+
+/// Median of each color channel over a square window, using sliding histograms.
+#[derive(Debug, Clone)]
+pub struct Median {
+    source: Vec<u8>,
+    radius: u32,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Median {
+    pub fn new(data: &[u8], width: u32, height: u32, radius: u32) -> Result<Self> {
+        if radius == 0 {
+            return Err(anyhow!("Median radius must be at least 1"));
+        }
+        if radius > MAX_RADIUS {
+            return Err(anyhow!("Median radius must be at most {}: {}", MAX_RADIUS, radius));
+        }
+        let cost = window_side(radius)?;
+        Ok(Self {
+            source: data.to_vec(),
+            radius,
+            size: (width, height),
+            cursor: RowCursor::with_cost(width, height, cost),
+        })
+    }
+
+    fn update_column(&self, histograms: &mut [[u32; 256]; 3], x: i64, y: u32, add: bool) {
+        let (width, height) = self.size;
+        let sx = EdgeMode::Clamp.index(x, width);
+        let radius = self.radius as i64;
+        for dy in -radius..=radius {
+            let sy = EdgeMode::Clamp.index(y as i64 + dy, height);
+            let index = pixel_index(sx, sy, width);
+            for (histogram, value) in histograms.iter_mut().zip(&self.source[index..index + 3]) {
+                if add {
+                    histogram[*value as usize] += 1;
+                } else {
+                    histogram[*value as usize] -= 1;
+                }
+            }
+        }
+    }
+
+    fn median_row(&self, y: u32, data: &mut [u8]) {
+        let width = self.size.0;
+        let radius = self.radius as i64;
+        let half = ((2 * radius + 1) * (2 * radius + 1) / 2) as u32;
+        let mut histograms = [[0; 256]; 3];
+        for x in -radius..=radius {
+            self.update_column(&mut histograms, x, y, true);
+        }
+        for x in 0..width {
+            let index = pixel_index(x, y, width);
+            for (value, histogram) in data[index..index + 3].iter_mut().zip(&histograms) {
+                let mut count = 0;
+                for (bin, entry) in histogram.iter().enumerate() {
+                    count += entry;
+                    if count > half {
+                        *value = bin as u8;
+                        break;
+                    }
+                }
+            }
+            self.update_column(&mut histograms, x as i64 + radius + 1, y, true);
+            self.update_column(&mut histograms, x as i64 - radius, y, false);
+        }
+    }
+}
+
+impl Filter for Median {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                self.median_row(y, data);
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+/// Edge preserving blur weighting neighbours by distance and color difference.
+#[derive(Debug, Clone)]
+pub struct Bilateral {
+    source: Vec<u8>,
+    radius: u32,
+    spatial_weights: Vec<f32>,
+    range_weights: Vec<f32>,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Bilateral {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               radius: u32, sigma_color: f64) -> Result<Self> {
+        if radius == 0 {
+            return Err(anyhow!("Bilateral radius must be at least 1"));
+        }
+        if radius > MAX_RADIUS {
+            return Err(anyhow!("Bilateral radius must be at most {}: {}", MAX_RADIUS, radius));
+        }
+        if sigma_color <= 0.0 {
+            return Err(anyhow!("Color sigma must be positive: {}", sigma_color));
+        }
+        let sigma_space = (radius as f64 / 2.0).max(0.5);
+        let radius_i = radius as i64;
+        let spatial_weights = (-radius_i..=radius_i)
+            .flat_map(|dy| (-radius_i..=radius_i).map(move |dx| (dx, dy)))
+            .map(|(dx, dy)| (-((dx * dx + dy * dy) as f64) / (2.0 * sigma_space * sigma_space)).exp() as f32)
+            .collect();
+        let range_weights = (0..=255 * 3)
+            .map(|distance| {
+                let distance = distance as f64 / 3.0;
+                (-(distance * distance) / (2.0 * sigma_color * sigma_color)).exp() as f32
+            })
+            .collect();
+        let cost = window_cost(radius, 1)?;
+        Ok(Self {
+            source: data.to_vec(),
+            radius,
+            spatial_weights,
+            range_weights,
+            size: (width, height),
+            cursor: RowCursor::with_cost(width, height, cost),
+        })
+    }
+}
+
+impl Filter for Bilateral {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.size;
+        let radius = self.radius as i64;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                for x in 0..width {
+                    let center_index = pixel_index(x, y, width);
+                    let center = &self.source[center_index..center_index + 3];
+                    let mut sum = [0.0; 3];
+                    let mut total = 0.0;
+                    let mut spatial = self.spatial_weights.iter();
+                    for dy in -radius..=radius {
+                        let sy = EdgeMode::Clamp.index(y as i64 + dy, height);
+                        for dx in -radius..=radius {
+                            let sx = EdgeMode::Clamp.index(x as i64 + dx, width);
+                            let index = pixel_index(sx, sy, width);
+                            let sample = &self.source[index..index + 3];
+                            let distance: usize = sample.iter().zip(center)
+                                .map(|(a, b)| a.abs_diff(*b) as usize)
+                                .sum();
+                            let weight = spatial.next().copied().unwrap_or(0.0) * self.range_weights[distance];
+                            for (sum, value) in sum.iter_mut().zip(sample) {
+                                *sum += *value as f32 * weight;
+                            }
+                            total += weight;
+                        }
+                    }
+                    for (value, sum) in data[center_index..center_index + 3].iter_mut().zip(sum) {
+                        *value = clamp_u8((sum / total) as f64);
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+/// Non-local means with 3x3 patches compared on luminance within a search window.
+#[derive(Debug, Clone)]
+pub struct NonLocalMeans {
+    source: Vec<u8>,
+    luminance: Vec<f32>,
+    search_radius: u32,
+    strength: f32,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl NonLocalMeans {
+    const PATCH_RADIUS: i64 = 1;
+
+    pub fn new(data: &[u8], width: u32, height: u32,
+               search_radius: u32, strength: f64) -> Result<Self> {
+        if search_radius == 0 {
+            return Err(anyhow!("Search radius must be at least 1"));
+        }
+        if search_radius > MAX_SEARCH_RADIUS {
+            return Err(anyhow!("Search radius must be at most {}: {}", MAX_SEARCH_RADIUS, search_radius));
+        }
+        if strength <= 0.0 {
+            return Err(anyhow!("Strength must be positive: {}", strength));
+        }
+        let cost = window_cost(search_radius, 9)?;
+        Ok(Self {
+            source: data.to_vec(),
+            luminance: data.chunks_exact(4)
+                .map(|pixel| luminance(pixel[0], pixel[1], pixel[2]) as f32)
+                .collect(),
+            search_radius,
+            strength: strength as f32,
+            size: (width, height),
+            cursor: RowCursor::with_cost(width, height, cost),
+        })
+    }
+
+    fn luminance_at(&self, x: i64, y: i64) -> f32 {
+        let (width, height) = self.size;
+        let x = EdgeMode::Clamp.index(x, width);
+        let y = EdgeMode::Clamp.index(y, height);
+        self.luminance[(y * width + x) as usize]
+    }
+
+    fn patch_distance(&self, (ax, ay): (i64, i64), (bx, by): (i64, i64)) -> f32 {
+        let mut sum = 0.0;
+        for dy in -Self::PATCH_RADIUS..=Self::PATCH_RADIUS {
+            for dx in -Self::PATCH_RADIUS..=Self::PATCH_RADIUS {
+                let difference = self.luminance_at(ax + dx, ay + dy) - self.luminance_at(bx + dx, by + dy);
+                sum += difference * difference;
+            }
+        }
+        let patch = (2 * Self::PATCH_RADIUS + 1) as f32;
+        sum / (patch * patch)
+    }
+}
+
+impl Filter for NonLocalMeans {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.size;
+        let radius = self.search_radius as i64;
+        let h2 = self.strength * self.strength;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                for x in 0..width {
+                    let center = (x as i64, y as i64);
+                    let mut sum = [0.0; 3];
+                    let mut total = 0.0;
+                    for dy in -radius..=radius {
+                        for dx in -radius..=radius {
+                            let candidate = (center.0 + dx, center.1 + dy);
+                            let weight = (-self.patch_distance(center, candidate) / h2).exp();
+                            let sx = EdgeMode::Clamp.index(candidate.0, width);
+                            let sy = EdgeMode::Clamp.index(candidate.1, height);
+                            let index = pixel_index(sx, sy, width);
+                            for (sum, value) in sum.iter_mut().zip(&self.source[index..index + 3]) {
+                                *sum += *value as f32 * weight;
+                            }
+                            total += weight;
+                        }
+                    }
+                    let index = pixel_index(x, y, width);
+                    for (value, sum) in data[index..index + 3].iter_mut().zip(sum) {
+                        *value = clamp_u8((sum / total) as f64);
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, Resize, ResizeFilter, Rotate, RotateFit, PIXELS_PER_STEP};
+use super::color::luminance;
+use super::resample::Interpolation;
+
+/// Long edge of the copy the skew is measured on.
+const ANALYSIS_SIZE: u32 = 1000;
+const COARSE_STEP: f64 = 0.5;
+const FINE_STEP: f64 = 0.05;
+
+/// Threshold maximizing the between-class variance of the histogram.
+fn otsu_threshold(histogram: &[u32; 256]) -> u8 {
+    let total: f64 = histogram.iter().map(|&count| count as f64).sum();
+    let weighted_total: f64 = histogram.iter().enumerate().map(|(value, &count)| value as f64 * count as f64).sum();
+    let (mut background, mut weighted_background) = (0.0, 0.0);
+    let (mut best, mut best_variance) = (0, -1.0);
+    for (value, &count) in histogram.iter().enumerate() {
+        background += count as f64;
+        weighted_background += value as f64 * count as f64;
+        let foreground = total - background;
+        if background == 0.0 || foreground == 0.0 {
+            continue;
+        }
+        let mean_background = weighted_background / background;
+        let mean_foreground = (weighted_total - weighted_background) / foreground;
+        let variance = background * foreground * (mean_background - mean_foreground).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = value as u8;
+        }
+    }
+    best
+}
+
+/// Dark pixels of the reduced copy of the image, binarized at the Otsu threshold.
+fn dark_points(small: &[u8], small_width: u32) -> Vec<(f64, f64)> {
+    let luminances: Vec<u8> = small
+        .chunks_exact(4)
+        .map(|pixel| luminance(pixel[0], pixel[1], pixel[2]).round() as u8)
+        .collect();
+    let mut histogram = [0; 256];
+    for &value in &luminances {
+        histogram[value as usize] += 1;
+    }
+    let threshold = otsu_threshold(&histogram);
+    luminances
+        .iter()
+        .enumerate()
+        .filter(|(_, &value)| value <= threshold)
+        .map(|(index, _)| ((index as u32 % small_width) as f64, (index as u32 / small_width) as f64))
+        .collect()
+}
+
+/// Sharpness of the horizontal projection profile of the points rotated back by `degrees`.
+fn profile_score(points: &[(f64, f64)], degrees: f64, bins: &mut [u32]) -> f64 {
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let offset = bins.len() as f64 / 2.0;
+    bins.fill(0);
+    for &(x, y) in points {
+        let row = (y * cos - x * sin + offset) as usize;
+        if let Some(bin) = bins.get_mut(row) {
+            *bin += 1;
+        }
+    }
+    bins.iter().map(|&count| (count as f64).powi(2)).sum()
+}
+
+/// Angles from `from` to `to` in steps of `step`, scored a few at a time.
+#[derive(Debug, Clone)]
+struct AngleSearch {
+    from: f64,
+    step: f64,
+    count: u32,
+    next: u32,
+    /// Best angle so far with its score.
+    best: (f64, f64),
+}
+
+impl AngleSearch {
+    fn new(from: f64, to: f64, step: f64) -> Self {
+        Self {
+            from,
+            step,
+            count: ((to - from) / step).round() as u32 + 1,
+            next: 0,
+            best: (0.0, f64::NEG_INFINITY),
+        }
+    }
+
+    /// Scores the next angles, and returns the best angle once all are scored.
+    fn step(&mut self, points: &[(f64, f64)], bins: &mut [u32]) -> Option<f64> {
+        let angles_per_step = (PIXELS_PER_STEP as usize / points.len().max(1)).max(1) as u32;
+        let end = (self.next + angles_per_step).min(self.count);
+        for i in self.next..end {
+            let angle = self.from + i as f64 * self.step;
+            let score = profile_score(points, angle, bins);
+            if score > self.best.1 {
+                self.best = (angle, score);
+            }
+        }
+        self.next = end;
+        (self.next >= self.count).then_some(self.best.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeskewStage {
+    Reduce,
+    Threshold,
+    CoarseSearch,
+    FineSearch,
+    Rotate,
+    Done,
+}
+
+/// Measures the clockwise tilt of the text lines within `max_degrees` either way,
+/// then rotates the image so they become horizontal.
+#[derive(Debug, Clone)]
+pub struct Deskew {
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    interpolation: Interpolation,
+    fit: RotateFit,
+    fill: [u8; 4],
+    /// Reduction to the copy the skew is measured on.
+    reduce: Resize,
+    small: Vec<u8>,
+    small_width: u32,
+    points: Vec<(f64, f64)>,
+    bins: Vec<u32>,
+    search: AngleSearch,
+    angle: Option<f64>,
+    rotate: Option<Rotate>,
+    /// Why the measured skew could not be corrected.
+    error: Option<String>,
+    stage: DeskewStage,
+}
+
+impl Deskew {
+    pub fn new(data: &[u8], width: u32, height: u32, max_degrees: f64,
+               interpolation: Interpolation, fit: RotateFit, fill: [u8; 4]) -> Result<Self> {
+        if !(max_degrees > 0.0 && max_degrees <= 45.0) {
+            return Err(anyhow!("Maximum angle must be within 0 and 45 degrees: {}", max_degrees));
+        }
+        let scale = (ANALYSIS_SIZE as f64 / width.max(height) as f64).min(1.0);
+        let small_width = ((width as f64 * scale).round() as u32).max(1);
+        let small_height = ((height as f64 * scale).round() as u32).max(1);
+        Ok(Self {
+            source: data.to_vec(),
+            source_size: (width, height),
+            interpolation,
+            fit,
+            fill,
+            reduce: Resize::new(data, width, height, small_width, small_height, ResizeFilter::Area)?,
+            small: vec![0; small_width as usize * small_height as usize * 4],
+            small_width,
+            points: Vec::new(),
+            // Rotated rows stay within the diagonal of the analysis copy on either side of zero.
+            bins: vec![0; 4 * ANALYSIS_SIZE as usize],
+            search: AngleSearch::new(-max_degrees, max_degrees, COARSE_STEP),
+            angle: None,
+            rotate: None,
+            error: None,
+            stage: DeskewStage::Reduce,
+        })
+    }
+
+    /// Detected clockwise skew in degrees, once the image is rotated by it.
+    /// Stays `None` when no text was found or the rotation failed, and the image is left as it is.
+    pub fn angle(&self) -> Option<f64> {
+        self.angle
+    }
+
+    /// Error of the rotation by the detected skew, when it could not be done.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn start_rotation(&mut self, angle: f64) -> Result<()> {
+        let (width, height) = self.source_size;
+        self.rotate = Some(Rotate::new(
+            &self.source, width, height, -angle, self.interpolation, self.fit, self.fill)?);
+        self.angle = Some(angle);
+        self.source = Vec::new();
+        self.points = Vec::new();
+        Ok(())
+    }
+}
+
+impl Filter for Deskew {
+    /// The source size until the skew is measured, the rotated size after.
+    fn output_size(&self) -> (u32, u32) {
+        self.rotate.as_ref().map_or(self.source_size, Rotate::output_size)
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        match self.stage {
+            DeskewStage::Reduce => {
+                if self.reduce.step(&mut self.small) {
+                    self.stage = DeskewStage::Threshold;
+                }
+            },
+            DeskewStage::Threshold => {
+                self.points = dark_points(&self.small, self.small_width);
+                self.small = Vec::new();
+                self.stage = if self.points.is_empty() {
+                    DeskewStage::Done
+                } else {
+                    DeskewStage::CoarseSearch
+                };
+            },
+            DeskewStage::CoarseSearch => {
+                if let Some(coarse) = self.search.step(&self.points, &mut self.bins) {
+                    self.search = AngleSearch::new(coarse - COARSE_STEP, coarse + COARSE_STEP, FINE_STEP);
+                    self.stage = DeskewStage::FineSearch;
+                }
+            },
+            DeskewStage::FineSearch => {
+                if let Some(angle) = self.search.step(&self.points, &mut self.bins) {
+                    self.stage = match self.start_rotation(angle) {
+                        Ok(()) => DeskewStage::Rotate,
+                        Err(err) => {
+                            self.error = Some(format!("Could not rotate by the detected skew {:.2}°: {:#}", angle, err));
+                            DeskewStage::Done
+                        },
+                    };
+                }
+            },
+            DeskewStage::Rotate => {
+                if self.rotate.as_mut().map_or(true, |rotate| rotate.step(data)) {
+                    self.stage = DeskewStage::Done;
+                }
+            },
+            DeskewStage::Done => {},
+        }
+        self.stage == DeskewStage::Done
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::run_to_end;
+
+    /// White page with rows of dark words tilted clockwise by `degrees`.
+    fn tilted_text(width: u32, height: u32, degrees: f64) -> Vec<u8> {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let (center_x, center_y) = (width as f64 / 2.0, height as f64 / 2.0);
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                let (dx, dy) = (x as f64 - center_x, y as f64 - center_y);
+                let along = dx * cos + dy * sin;
+                let across = dy * cos - dx * sin;
+                let inside = along.abs() < width as f64 * 0.35 && across.abs() < height as f64 * 0.35;
+                let dark = inside && across.rem_euclid(16.0) < 2.0 && along.rem_euclid(30.0) < 22.0;
+                let value = if dark { 20 } else { 240 };
+                data.extend([value, value, value, 255]);
+            }
+        }
+        data
+    }
+
+    fn deskew(data: &[u8], size: (u32, u32), max_degrees: f64) -> (Deskew, Vec<u8>) {
+        let mut deskew = Deskew::new(
+            data, size.0, size.1, max_degrees, Interpolation::Bilinear, RotateFit::Expand, [255; 4]).unwrap();
+        let output = run_to_end(&mut deskew, data, size);
+        (deskew, output)
+    }
+
+    #[test]
+    fn estimate_skew_finds_the_tilt() {
+        for degrees in [3.0, -5.0, 0.0, 12.5] {
+            let data = tilted_text(600, 400, degrees);
+            let (deskew, _) = deskew(&data, (600, 400), 15.0);
+            let angle = deskew.angle().unwrap();
+            // Rows of the profile are one pixel of the analysis copy, which limits the precision.
+            assert!((angle - degrees).abs() <= 0.15, "found {} for {}", angle, degrees);
+        }
+    }
+
+    #[test]
+    fn output_is_rotated_by_the_detected_angle() {
+        let data = tilted_text(400, 300, 4.0);
+        let (deskew, output) = deskew(&data, (400, 300), 10.0);
+        let size = deskew.output_size();
+        assert!(size.0 > 400 && size.1 > 300);
+        assert_eq!(output.len(), size.0 as usize * size.1 as usize * 4);
+    }
+
+    #[test]
+    fn page_without_text_is_left_as_it_is() {
+        let data = vec![240; 50 * 40 * 4];
+        let (deskew, output) = deskew(&data, (50, 40), 10.0);
+        assert_eq!(deskew.angle(), None);
+        assert_eq!(deskew.output_size(), (50, 40));
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn failed_rotation_is_reported() {
+        let data = vec![240; 50 * 40 * 4];
+        let mut deskew = Deskew::new(
+            &data, 50, 40, 10.0, Interpolation::Bilinear, RotateFit::Expand, [255; 4]).unwrap();
+        // A search whose only angle is not a number, which the rotation rejects.
+        deskew.search = AngleSearch::new(f64::NAN, f64::NAN, 1.0);
+        deskew.search.count = 1;
+        deskew.stage = DeskewStage::FineSearch;
+        let output = run_to_end(&mut deskew, &data, (50, 40));
+        assert_eq!(deskew.angle(), None);
+        assert!(deskew.error().is_some());
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn maximum_angle_is_checked() {
+        let data = [0; 4];
+        for max_degrees in [0.0, -1.0, 46.0, f64::NAN] {
+            assert!(Deskew::new(
+                &data, 1, 1, max_degrees, Interpolation::Bilinear, RotateFit::Expand, [0; 4]).is_err());
+        }
+    }
+}
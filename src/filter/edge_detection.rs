@@ -0,0 +1,383 @@
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode, Blur, BlurKind};
+use super::color::{clamp_u8, luminance, hsv_to_rgb};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GradientOperator {
+    Sobel,
+    Prewitt,
+}
+
+impl GradientOperator {
+    /// Weight of the center row or column; the other two have weight 1.
+    fn center_weight(self) -> f32 {
+        match self {
+            GradientOperator::Sobel => 2.0,
+            GradientOperator::Prewitt => 1.0,
+        }
+    }
+}
+
+impl FromStr for GradientOperator {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "sobel" => Ok(GradientOperator::Sobel),
+            "prewitt" => Ok(GradientOperator::Prewitt),
+            _ => Err(anyhow!("Unknown gradient operator {:#?}", value)),
+        }
+    }
+}
+
+/// Single channel image sampled with clamped edges.
+#[derive(Debug, Clone)]
+struct Plane {
+    values: Vec<f32>,
+    width: u32,
+    height: u32,
+}
+
+impl Plane {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            values: vec![0.0; width as usize * height as usize],
+            width,
+            height,
+        }
+    }
+
+    /// Luminance of an RGBA buffer.
+    fn from_rgba(data: &[u8], width: u32, height: u32) -> Self {
+        Self {
+            values: data.chunks_exact(4)
+                .map(|pixel| luminance(pixel[0], pixel[1], pixel[2]) as f32)
+                .collect(),
+            width,
+            height,
+        }
+    }
+
+    fn get(&self, x: i64, y: i64) -> f32 {
+        let x = EdgeMode::Clamp.index(x, self.width);
+        let y = EdgeMode::Clamp.index(y, self.height);
+        self.values[(y * self.width + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, value: f32) {
+        self.values[(y * self.width + x) as usize] = value;
+    }
+
+    /// Normalized horizontal and vertical derivatives at `(x, y)`.
+    fn gradient(&self, x: u32, y: u32, operator: GradientOperator) -> (f32, f32) {
+        let (x, y) = (x as i64, y as i64);
+        let center = operator.center_weight();
+        let gx = (self.get(x + 1, y - 1) + center * self.get(x + 1, y) + self.get(x + 1, y + 1))
+            - (self.get(x - 1, y - 1) + center * self.get(x - 1, y) + self.get(x - 1, y + 1));
+        let gy = (self.get(x - 1, y + 1) + center * self.get(x, y + 1) + self.get(x + 1, y + 1))
+            - (self.get(x - 1, y - 1) + center * self.get(x, y - 1) + self.get(x + 1, y - 1));
+        let scale = center + 2.0;
+        (gx / scale, gy / scale)
+    }
+}
+
+fn gray_rgba(data: &[u8]) -> Vec<u8> {
+    data.chunks_exact(4)
+        .flat_map(|pixel| {
+            let value = clamp_u8(luminance(pixel[0], pixel[1], pixel[2]));
+            [value, value, value, 255]
+        })
+        .collect()
+}
+
+fn write_gray(data: &mut [u8], index: usize, value: u8) {
+    data[index * 4..index * 4 + 4].copy_from_slice(&[value, value, value, 255]);
+}
+
+/// Gradient magnitude, optionally colored by the gradient direction.
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    plane: Plane,
+    operator: GradientOperator,
+    direction_coloring: bool,
+    cursor: RowCursor,
+}
+
+impl Gradient {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               operator: GradientOperator, direction_coloring: bool) -> Self {
+        Self {
+            plane: Plane::from_rgba(data, width, height),
+            operator,
+            direction_coloring,
+            cursor: RowCursor::new(width, height),
+        }
+    }
+}
+
+impl Filter for Gradient {
+    fn output_size(&self) -> (u32, u32) {
+        (self.plane.width, self.plane.height)
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let width = self.plane.width;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                for x in 0..width {
+                    let (gx, gy) = self.plane.gradient(x, y, self.operator);
+                    let magnitude = (gx * gx + gy * gy).sqrt().min(255.0);
+                    let index = (y * width + x) as usize;
+                    if self.direction_coloring {
+                        let hue = (gy.atan2(gx).to_degrees() + 360.0) % 360.0;
+                        let [r, g, b] = hsv_to_rgb(hue as f64, 1.0, magnitude as f64 / 255.0);
+                        data[index * 4..index * 4 + 4].copy_from_slice(&[r, g, b, 255]);
+                    } else {
+                        write_gray(data, index, clamp_u8(magnitude as f64));
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LaplacianStage {
+    Blur,
+    Laplacian,
+    ZeroCrossing,
+}
+
+/// Laplacian of Gaussian; zero crossings steeper than `threshold` become white.
+#[derive(Debug, Clone)]
+pub struct LaplacianOfGaussian {
+    blur: Blur,
+    blurred: Vec<u8>,
+    plane: Plane,
+    laplacian: Plane,
+    threshold: f32,
+    stage: LaplacianStage,
+    cursor: RowCursor,
+}
+
+impl LaplacianOfGaussian {
+    pub fn new(data: &[u8], width: u32, height: u32, radius: u32, threshold: f64) -> Result<Self> {
+        let gray = gray_rgba(data);
+        Ok(Self {
+            blur: Blur::new(BlurKind::Gaussian, &gray, width, height, radius, EdgeMode::Clamp)?,
+            blurred: gray,
+            plane: Plane::new(0, 0),
+            laplacian: Plane::new(width, height),
+            threshold: threshold as f32,
+            stage: LaplacianStage::Blur,
+            cursor: RowCursor::new(width, height),
+        })
+    }
+}
+
+impl Filter for LaplacianOfGaussian {
+    fn output_size(&self) -> (u32, u32) {
+        (self.laplacian.width, self.laplacian.height)
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.output_size();
+        match self.stage {
+            LaplacianStage::Blur => {
+                if self.blur.step(&mut self.blurred) {
+                    self.plane = Plane::from_rgba(&self.blurred, width, height);
+                    self.stage = LaplacianStage::Laplacian;
+                }
+            },
+            LaplacianStage::Laplacian => {
+                let blurred = &self.plane;
+                if let Some(rows) = self.cursor.next_rows() {
+                    for y in rows {
+                        for x in 0..width {
+                            let (xi, yi) = (x as i64, y as i64);
+                            let value = blurred.get(xi - 1, yi) + blurred.get(xi + 1, yi)
+                                + blurred.get(xi, yi - 1) + blurred.get(xi, yi + 1)
+                                - 4.0 * blurred.get(xi, yi);
+                            self.laplacian.set(x, y, value);
+                        }
+                    }
+                }
+                if self.cursor.is_done() {
+                    self.stage = LaplacianStage::ZeroCrossing;
+                    self.cursor = RowCursor::new(width, height);
+                }
+            },
+            LaplacianStage::ZeroCrossing => {
+                if let Some(rows) = self.cursor.next_rows() {
+                    for y in rows {
+                        for x in 0..width {
+                            let (xi, yi) = (x as i64, y as i64);
+                            let center = self.laplacian.get(xi, yi);
+                            let crossing = [(1, 0), (0, 1), (1, 1), (1, -1)].iter().any(|(dx, dy)| {
+                                let next = self.laplacian.get(xi + dx, yi + dy);
+                                center * next < 0.0 && (center - next).abs() >= self.threshold
+                            });
+                            write_gray(data, (y * width + x) as usize, if crossing { 255 } else { 0 });
+                        }
+                    }
+                }
+                return self.cursor.is_done();
+            },
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CannyStage {
+    Blur,
+    Gradient,
+    Suppression,
+    Hysteresis,
+    Output,
+}
+
+/// Canny edge detector with hysteresis between `low` and `high` gradient magnitudes.
+#[derive(Debug, Clone)]
+pub struct Canny {
+    blur: Blur,
+    blurred: Vec<u8>,
+    plane: Plane,
+    magnitude: Plane,
+    direction: Vec<u8>,
+    suppressed: Plane,
+    edges: Vec<bool>,
+    low: f32,
+    high: f32,
+    stage: CannyStage,
+    cursor: RowCursor,
+}
+
+impl Canny {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               radius: u32, low: f64, high: f64) -> Result<Self> {
+        if low < 0.0 || high < low {
+            return Err(anyhow!("Thresholds must satisfy 0 <= low <= high: {}, {}", low, high));
+        }
+        let gray = gray_rgba(data);
+        Ok(Self {
+            blur: Blur::new(BlurKind::Gaussian, &gray, width, height, radius, EdgeMode::Clamp)?,
+            blurred: gray,
+            plane: Plane::new(0, 0),
+            magnitude: Plane::new(width, height),
+            direction: vec![0; width as usize * height as usize],
+            suppressed: Plane::new(width, height),
+            edges: Vec::new(),
+            low: low as f32,
+            high: high as f32,
+            stage: CannyStage::Blur,
+            cursor: RowCursor::new(width, height),
+        })
+    }
+
+    /// Gradient direction rounded to 0, 45, 90 or 135 degrees.
+    fn quantize_direction(gx: f32, gy: f32) -> u8 {
+        let angle = (gy.atan2(gx).to_degrees() + 180.0) % 180.0;
+        ((angle + 22.5) / 45.0) as u8 % 4
+    }
+
+    fn hysteresis(&mut self) {
+        let (width, height) = (self.suppressed.width, self.suppressed.height);
+        self.edges = vec![false; self.suppressed.values.len()];
+        let mut stack: Vec<usize> = self.suppressed.values.iter()
+            .enumerate()
+            .filter(|(_, value)| **value >= self.high)
+            .map(|(index, _)| index)
+            .collect();
+        for index in &stack {
+            self.edges[*index] = true;
+        }
+        while let Some(index) = stack.pop() {
+            let (x, y) = ((index as u32 % width) as i64, (index as u32 / width) as i64);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width as i64 || ny >= height as i64 {
+                        continue;
+                    }
+                    let neighbour = (ny as u32 * width + nx as u32) as usize;
+                    if !self.edges[neighbour] && self.suppressed.values[neighbour] >= self.low {
+                        self.edges[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Filter for Canny {
+    fn output_size(&self) -> (u32, u32) {
+        (self.magnitude.width, self.magnitude.height)
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.output_size();
+        match self.stage {
+            CannyStage::Blur => {
+                if self.blur.step(&mut self.blurred) {
+                    self.plane = Plane::from_rgba(&self.blurred, width, height);
+                    self.stage = CannyStage::Gradient;
+                }
+            },
+            CannyStage::Gradient => {
+                if let Some(rows) = self.cursor.next_rows() {
+                    for y in rows {
+                        for x in 0..width {
+                            let (gx, gy) = self.plane.gradient(x, y, GradientOperator::Sobel);
+                            self.magnitude.set(x, y, (gx * gx + gy * gy).sqrt());
+                            self.direction[(y * width + x) as usize] = Self::quantize_direction(gx, gy);
+                        }
+                    }
+                }
+                if self.cursor.is_done() {
+                    self.stage = CannyStage::Suppression;
+                    self.cursor = RowCursor::new(width, height);
+                }
+            },
+            CannyStage::Suppression => {
+                if let Some(rows) = self.cursor.next_rows() {
+                    for y in rows {
+                        for x in 0..width {
+                            let (xi, yi) = (x as i64, y as i64);
+                            let (dx, dy) = match self.direction[(y * width + x) as usize] {
+                                0 => (1, 0),
+                                1 => (1, 1),
+                                2 => (0, 1),
+                                _ => (-1, 1),
+                            };
+                            let value = self.magnitude.get(xi, yi);
+                            let is_peak = value >= self.magnitude.get(xi + dx, yi + dy)
+                                && value > self.magnitude.get(xi - dx, yi - dy);
+                            self.suppressed.set(x, y, if is_peak { value } else { 0.0 });
+                        }
+                    }
+                }
+                if self.cursor.is_done() {
+                    self.stage = CannyStage::Hysteresis;
+                }
+            },
+            CannyStage::Hysteresis => {
+                self.hysteresis();
+                self.stage = CannyStage::Output;
+                self.cursor = RowCursor::new(width, height);
+            },
+            CannyStage::Output => {
+                if let Some(rows) = self.cursor.next_rows() {
+                    for index in (rows.start * width) as usize..(rows.end * width) as usize {
+                        write_gray(data, index, if self.edges[index] { 255 } else { 0 });
+                    }
+                }
+                return self.cursor.is_done();
+            },
+        }
+        false
+    }
+}
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+use anyhow::{Error, anyhow};
+
+/// How samples outside of the image are read by neighbourhood filters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeMode {
+    Clamp,
+    Mirror,
+    Wrap,
+}
+
+impl EdgeMode {
+    /// Maps a possibly out of range coordinate to `0..length`.
+    pub fn index(self, position: i64, length: u32) -> u32 {
+        let length = length as i64;
+        if length <= 1 {
+            return 0;
+        }
+        let index = match self {
+            EdgeMode::Clamp => position.clamp(0, length - 1),
+            EdgeMode::Mirror => {
+                let period = 2 * (length - 1);
+                let folded = position.rem_euclid(period);
+                if folded < length { folded } else { period - folded }
+            },
+            EdgeMode::Wrap => position.rem_euclid(length),
+        };
+        index as u32
+    }
+}
+
+impl FromStr for EdgeMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "clamp" => Ok(EdgeMode::Clamp),
+            "mirror" => Ok(EdgeMode::Mirror),
+            "wrap" => Ok(EdgeMode::Wrap),
+            _ => Err(anyhow!("Unknown edge mode {:#?}", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_keeps_positions_inside() {
+        for mode in [EdgeMode::Clamp, EdgeMode::Mirror, EdgeMode::Wrap] {
+            for position in 0..5 {
+                assert_eq!(mode.index(position, 5), position as u32);
+            }
+        }
+    }
+
+    #[test]
+    fn index_maps_positions_outside() {
+        let indices = |mode: EdgeMode| -> Vec<u32> {
+            (-3..8).map(|position| mode.index(position, 5)).collect()
+        };
+        assert_eq!(indices(EdgeMode::Clamp), [0, 0, 0, 0, 1, 2, 3, 4, 4, 4, 4]);
+        assert_eq!(indices(EdgeMode::Mirror), [3, 2, 1, 0, 1, 2, 3, 4, 3, 2, 1]);
+        assert_eq!(indices(EdgeMode::Wrap), [2, 3, 4, 0, 1, 2, 3, 4, 0, 1, 2]);
+    }
+
+    #[test]
+    fn index_of_single_pixel_is_zero() {
+        for mode in [EdgeMode::Clamp, EdgeMode::Mirror, EdgeMode::Wrap] {
+            assert_eq!(mode.index(-7, 1), 0);
+            assert_eq!(mode.index(9, 1), 0);
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, row_bytes};
+use super::color::{luminance, set_luminance};
+
+type Lut = [u8; 256];
+
+fn luminance_index(pixel: &[u8]) -> usize {
+    luminance(pixel[0], pixel[1], pixel[2]).round() as usize
+}
+
+fn equalize_lut(histogram: &[u32; 256]) -> Lut {
+    let total: u32 = histogram.iter().sum();
+    let mut lut = [0; 256];
+    let cdf_min = histogram.iter().copied().find(|&count| count > 0).unwrap_or(0);
+    if total == cdf_min {
+        for (value, entry) in lut.iter_mut().enumerate() {
+            *entry = value as u8;
+        }
+        return lut;
+    }
+    let mut cdf = 0;
+    for (value, count) in histogram.iter().enumerate() {
+        cdf += count;
+        let scaled = (cdf.saturating_sub(cdf_min)) as f64 / (total - cdf_min) as f64;
+        lut[value] = (scaled * 255.0).round() as u8;
+    }
+    lut
+}
+
+fn clip_histogram(histogram: &mut [u32; 256], limit: u32) {
+    let mut excess = 0;
+    for count in histogram.iter_mut() {
+        if *count > limit {
+            excess += *count - limit;
+            *count = limit;
+        }
+    }
+    let share = excess / 256;
+    let remainder = (excess % 256) as usize;
+    for (value, count) in histogram.iter_mut().enumerate() {
+        *count += share;
+        if value < remainder {
+            *count += 1;
+        }
+    }
+}
+
+fn cumulative_lut(histogram: &[u32; 256]) -> Lut {
+    let total: u32 = histogram.iter().sum();
+    let mut lut = [0; 256];
+    let mut cdf = 0;
+    for (value, count) in histogram.iter().enumerate() {
+        cdf += count;
+        lut[value] = (cdf as f64 * 255.0 / total.max(1) as f64).round() as u8;
+    }
+    lut
+}
+
+/// Global histogram equalization of the luminance channel.
+#[derive(Debug, Clone)]
+pub struct HistogramEqualization {
+    lut: Lut,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl HistogramEqualization {
+    pub fn new(data: &[u8], width: u32, height: u32) -> Self {
+        let mut histogram = [0; 256];
+        for pixel in data.chunks_exact(4) {
+            histogram[luminance_index(pixel)] += 1;
+        }
+        Self {
+            lut: equalize_lut(&histogram),
+            size: (width, height),
+            cursor: RowCursor::new(width, height),
+        }
+    }
+}
+
+impl Filter for HistogramEqualization {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.cursor.next_rows() {
+            for pixel in data[row_bytes(&rows, self.size.0)].chunks_exact_mut(4) {
+                let target = self.lut[luminance_index(pixel)];
+                set_luminance(pixel, target as f64);
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+/// Most tiles per side, each of which keeps its own lookup table.
+const MAX_TILE_GRID: u32 = 64;
+
+/// Contrast Limited Adaptive Histogram Equalization of the luminance channel.
+#[derive(Debug, Clone)]
+pub struct Clahe {
+    luts: Vec<Lut>,
+    tiles: (u32, u32),
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Clahe {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               tile_grid: u32, clip_limit: f64) -> Result<Self> {
+        if tile_grid == 0 {
+            return Err(anyhow!("Tile grid size must be at least 1"));
+        }
+        if tile_grid > MAX_TILE_GRID {
+            return Err(anyhow!("Tile grid size must be at most {}: {}", MAX_TILE_GRID, tile_grid));
+        }
+        if clip_limit <= 0.0 {
+            return Err(anyhow!("Clip limit must be positive: {}", clip_limit));
+        }
+        let tiles = (tile_grid.min(width.max(1)), tile_grid.min(height.max(1)));
+        let mut luts = Vec::with_capacity((tiles.0 * tiles.1) as usize);
+        for ty in 0..tiles.1 {
+            let (y0, y1) = (ty * height / tiles.1, (ty + 1) * height / tiles.1);
+            for tx in 0..tiles.0 {
+                let (x0, x1) = (tx * width / tiles.0, (tx + 1) * width / tiles.0);
+                let mut histogram = [0; 256];
+                for y in y0..y1 {
+                    let start = ((y * width + x0) * 4) as usize;
+                    let end = ((y * width + x1) * 4) as usize;
+                    for pixel in data[start..end].chunks_exact(4) {
+                        histogram[luminance_index(pixel)] += 1;
+                    }
+                }
+                let tile_pixels = (x1 - x0) * (y1 - y0);
+                let limit = (clip_limit * tile_pixels as f64 / 256.0).ceil().max(1.0);
+                clip_histogram(&mut histogram, limit as u32);
+                luts.push(cumulative_lut(&histogram));
+            }
+        }
+        Ok(Self {
+            luts,
+            tiles,
+            size: (width, height),
+            cursor: RowCursor::new(width, height),
+        })
+    }
+
+    /// Neighbouring tile indices and the interpolation weight along one axis.
+    fn tile_position(position: u32, length: u32, tiles: u32) -> (usize, usize, f64) {
+        let scaled = (position as f64 + 0.5) * tiles as f64 / length as f64 - 0.5;
+        if scaled <= 0.0 {
+            return (0, 0, 0.0);
+        }
+        let first = scaled.floor() as u32;
+        if first >= tiles - 1 {
+            let last = (tiles - 1) as usize;
+            return (last, last, 0.0);
+        }
+        (first as usize, first as usize + 1, scaled - first as f64)
+    }
+
+    fn lut(&self, tx: usize, ty: usize) -> &Lut {
+        &self.luts[ty * self.tiles.0 as usize + tx]
+    }
+}
+
+impl Filter for Clahe {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.size;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                let (ty0, ty1, wy) = Self::tile_position(y, height, self.tiles.1);
+                for x in 0..width {
+                    let (tx0, tx1, wx) = Self::tile_position(x, width, self.tiles.0);
+                    let index = ((y * width + x) * 4) as usize;
+                    let pixel = &mut data[index..index + 4];
+                    let value = luminance_index(pixel);
+                    let top = self.lut(tx0, ty0)[value] as f64 * (1.0 - wx)
+                        + self.lut(tx1, ty0)[value] as f64 * wx;
+                    let bottom = self.lut(tx0, ty1)[value] as f64 * (1.0 - wx)
+                        + self.lut(tx1, ty1)[value] as f64 * wx;
+                    set_luminance(pixel, top * (1.0 - wy) + bottom * wy);
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
@@ -0,0 +1,265 @@
+use std::ops::Range;
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode, row_bytes, PIXELS_PER_STEP};
+use super::color::luminance;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuringElement {
+    Square,
+    Cross,
+    Disk,
+}
+
+impl StructuringElement {
+    /// Offsets covered by the element of the given radius.
+    fn offsets(self, radius: u32) -> Vec<(i64, i64)> {
+        let radius = radius as i64;
+        (-radius..=radius)
+            .flat_map(|dy| (-radius..=radius).map(move |dx| (dx, dy)))
+            .filter(|&(dx, dy)| match self {
+                StructuringElement::Square => true,
+                StructuringElement::Cross => dx == 0 || dy == 0,
+                StructuringElement::Disk => dx * dx + dy * dy <= radius * radius,
+            })
+            .collect()
+    }
+}
+
+impl FromStr for StructuringElement {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "square" => Ok(StructuringElement::Square),
+            "cross" => Ok(StructuringElement::Cross),
+            "disk" => Ok(StructuringElement::Disk),
+            _ => Err(anyhow!("Unknown structuring element {:#?}", value)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorphologyOperation {
+    Erode,
+    Dilate,
+    Open,
+    Close,
+    TopHat,
+    BlackHat,
+}
+
+impl MorphologyOperation {
+    /// Whether each pass takes the maximum (dilation) or the minimum (erosion).
+    fn passes(self) -> Vec<bool> {
+        match self {
+            MorphologyOperation::Erode => vec![false],
+            MorphologyOperation::Dilate => vec![true],
+            MorphologyOperation::Open | MorphologyOperation::TopHat => vec![false, true],
+            MorphologyOperation::Close | MorphologyOperation::BlackHat => vec![true, false],
+        }
+    }
+}
+
+impl FromStr for MorphologyOperation {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "erode" => Ok(MorphologyOperation::Erode),
+            "dilate" => Ok(MorphologyOperation::Dilate),
+            "open" => Ok(MorphologyOperation::Open),
+            "close" => Ok(MorphologyOperation::Close),
+            "top_hat" => Ok(MorphologyOperation::TopHat),
+            "black_hat" => Ok(MorphologyOperation::BlackHat),
+            _ => Err(anyhow!("Unknown morphology operation {:#?}", value)),
+        }
+    }
+}
+
+/// Largest structuring element radius.
+const MAX_RADIUS: u32 = 20;
+
+/// Grayscale morphology on each color channel, which is the binary one on black and white images.
+#[derive(Debug, Clone)]
+pub struct Morphology {
+    operation: MorphologyOperation,
+    offsets: Vec<(i64, i64)>,
+    passes: Vec<bool>,
+    pass: usize,
+    source: Vec<u8>,
+    current: Vec<u8>,
+    next: Vec<u8>,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Morphology {
+    pub fn new(operation: MorphologyOperation, element: StructuringElement,
+               data: &[u8], width: u32, height: u32, radius: u32) -> Result<Self> {
+        if radius == 0 {
+            return Err(anyhow!("Structuring element radius must be at least 1"));
+        }
+        if radius > MAX_RADIUS {
+            return Err(anyhow!("Structuring element radius must be at most {}: {}", MAX_RADIUS, radius));
+        }
+        let offsets = element.offsets(radius);
+        Ok(Self {
+            operation,
+            cursor: RowCursor::with_cost(width, height, offsets.len() as u32),
+            offsets,
+            passes: operation.passes(),
+            pass: 0,
+            source: data.to_vec(),
+            current: data.to_vec(),
+            next: data.to_vec(),
+            size: (width, height),
+        })
+    }
+
+    fn pass_row(&mut self, y: u32, dilate: bool) {
+        let (width, height) = self.size;
+        let current = &self.current;
+        let output = &mut self.next[row_bytes(&(y..y + 1), width)];
+        for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+            let mut extreme = if dilate { [0; 3] } else { [255; 3] };
+            for (dx, dy) in &self.offsets {
+                let sx = EdgeMode::Clamp.index(x as i64 + dx, width);
+                let sy = EdgeMode::Clamp.index(y as i64 + dy, height);
+                let index = (sy * width + sx) as usize * 4;
+                for (extreme, value) in extreme.iter_mut().zip(&current[index..index + 3]) {
+                    *extreme = if dilate { (*extreme).max(*value) } else { (*extreme).min(*value) };
+                }
+            }
+            pixel[..3].copy_from_slice(&extreme);
+        }
+    }
+
+    fn output_rows(&self, rows: &Range<u32>, data: &mut [u8]) {
+        let range = row_bytes(rows, self.size.0);
+        let output = data[range.clone()].chunks_exact_mut(4);
+        let result = self.current[range.clone()].chunks_exact(4);
+        let source = self.source[range].chunks_exact(4);
+        for ((pixel, result), source) in output.zip(result).zip(source) {
+            for channel in 0..3 {
+                pixel[channel] = match self.operation {
+                    MorphologyOperation::TopHat => source[channel].saturating_sub(result[channel]),
+                    MorphologyOperation::BlackHat => result[channel].saturating_sub(source[channel]),
+                    _ => result[channel],
+                };
+            }
+        }
+    }
+}
+
+impl Filter for Morphology {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.size;
+        if let Some(&dilate) = self.passes.get(self.pass) {
+            if let Some(rows) = self.cursor.next_rows() {
+                for y in rows {
+                    self.pass_row(y, dilate);
+                }
+            }
+            if self.cursor.is_done() {
+                std::mem::swap(&mut self.current, &mut self.next);
+                self.pass += 1;
+                self.cursor = if self.pass < self.passes.len() {
+                    RowCursor::with_cost(width, height, self.offsets.len() as u32)
+                } else {
+                    RowCursor::new(width, height)
+                };
+            }
+            return false;
+        }
+        if let Some(rows) = self.cursor.next_rows() {
+            self.output_rows(&rows, data);
+        }
+        self.cursor.is_done()
+    }
+}
+
+/// Flips 8-connected dark or light components smaller than `min_size` pixels
+/// to the opposite color, removing specks and filling small holes.
+#[derive(Debug, Clone)]
+pub struct RemoveSpecks {
+    light: Vec<bool>,
+    visited: Vec<bool>,
+    next_seed: usize,
+    min_size: usize,
+    size: (u32, u32),
+}
+
+impl RemoveSpecks {
+    pub fn new(data: &[u8], width: u32, height: u32, min_size: u32) -> Self {
+        let light: Vec<bool> = data
+            .chunks_exact(4)
+            .map(|pixel| luminance(pixel[0], pixel[1], pixel[2]) >= 128.0)
+            .collect();
+        Self {
+            visited: vec![false; light.len()],
+            light,
+            next_seed: 0,
+            min_size: min_size as usize,
+            size: (width, height),
+        }
+    }
+
+    fn component(&mut self, seed: usize) -> Vec<usize> {
+        let (width, height) = (self.size.0 as i64, self.size.1 as i64);
+        let color = self.light[seed];
+        let mut component = vec![seed];
+        let mut stack = vec![seed];
+        self.visited[seed] = true;
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index as i64 % width, index as i64 / width);
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (nx, ny) = (x + dx, y + dy);
+                    if nx < 0 || ny < 0 || nx >= width || ny >= height {
+                        continue;
+                    }
+                    let neighbor = (ny * width + nx) as usize;
+                    if !self.visited[neighbor] && self.light[neighbor] == color {
+                        self.visited[neighbor] = true;
+                        component.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+        component
+    }
+}
+
+impl Filter for RemoveSpecks {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let mut budget = PIXELS_PER_STEP as usize;
+        while self.next_seed < self.light.len() && budget > 0 {
+            let seed = self.next_seed;
+            self.next_seed += 1;
+            budget -= 1;
+            if self.visited[seed] {
+                continue;
+            }
+            let component = self.component(seed);
+            budget = budget.saturating_sub(component.len());
+            if component.len() >= self.min_size {
+                continue;
+            }
+            let value = if self.light[seed] { 0 } else { 255 };
+            for index in component {
+                data[index * 4..index * 4 + 3].fill(value);
+            }
+        }
+        self.next_seed >= self.light.len()
+    }
+}
@@ -0,0 +1,112 @@
+use super::{Filter, RowCursor, row_bytes};
+
+/// Lossless quarter turn or mirror of the image. Rotations are clockwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipHorizontal,
+    FlipVertical,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reorient {
+    orientation: Orientation,
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Reorient {
+    pub fn new(orientation: Orientation, data: &[u8], width: u32, height: u32) -> Self {
+        let size = match orientation {
+            Orientation::Rotate90 | Orientation::Rotate270 => (height, width),
+            _ => (width, height),
+        };
+        Self {
+            orientation,
+            source: data.to_vec(),
+            source_size: (width, height),
+            size,
+            cursor: RowCursor::new(size.0, size.1),
+        }
+    }
+
+    /// Source pixel shown at `(x, y)` of the output.
+    fn source_position(&self, x: u32, y: u32) -> (u32, u32) {
+        let (width, height) = self.source_size;
+        match self.orientation {
+            Orientation::Rotate90 => (y, height - 1 - x),
+            Orientation::Rotate180 => (width - 1 - x, height - 1 - y),
+            Orientation::Rotate270 => (width - 1 - y, x),
+            Orientation::FlipHorizontal => (width - 1 - x, y),
+            Orientation::FlipVertical => (x, height - 1 - y),
+        }
+    }
+}
+
+impl Filter for Reorient {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let width = self.size.0;
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                let output = &mut data[row_bytes(&(y..y + 1), width)];
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    let (sx, sy) = self.source_position(x as u32, y);
+                    let index = (sy * self.source_size.0 + sx) as usize * 4;
+                    pixel.copy_from_slice(&self.source[index..index + 4]);
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::run_to_end;
+
+    /// 3x2 image whose red channel numbers the pixels row by row.
+    fn numbered() -> Vec<u8> {
+        (0..6).flat_map(|value| [value, 0, 0, 255]).collect()
+    }
+
+    fn reorient(orientation: Orientation) -> ((u32, u32), Vec<u8>) {
+        let mut filter = Reorient::new(orientation, &numbered(), 3, 2);
+        let data = run_to_end(&mut filter, &numbered(), (3, 2));
+        let size = filter.output_size();
+        (size, data.chunks_exact(4).map(|pixel| pixel[0]).collect())
+    }
+
+    #[test]
+    fn quarter_turns_swap_the_size() {
+        assert_eq!(reorient(Orientation::Rotate90), ((2, 3), vec![3, 0, 4, 1, 5, 2]));
+        assert_eq!(reorient(Orientation::Rotate270), ((2, 3), vec![2, 5, 1, 4, 0, 3]));
+    }
+
+    #[test]
+    fn half_turn_and_flips_keep_the_size() {
+        assert_eq!(reorient(Orientation::Rotate180), ((3, 2), vec![5, 4, 3, 2, 1, 0]));
+        assert_eq!(reorient(Orientation::FlipHorizontal), ((3, 2), vec![2, 1, 0, 5, 4, 3]));
+        assert_eq!(reorient(Orientation::FlipVertical), ((3, 2), vec![3, 4, 5, 0, 1, 2]));
+    }
+
+    #[test]
+    fn four_quarter_turns_give_the_source() {
+        let mut data = numbered();
+        let mut size = (3, 2);
+        for _ in 0..4 {
+            let mut filter = Reorient::new(Orientation::Rotate90, &data, size.0, size.1);
+            data = run_to_end(&mut filter, &data, size);
+            size = filter.output_size();
+        }
+        assert_eq!((size, data), ((3, 2), numbered()));
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, row_bytes, check_image_size};
+use super::resample::{Interpolation, Sampler};
+
+/// Projective mapping from output pixels to source pixels.
+#[derive(Debug, Clone, Copy)]
+struct Homography([f64; 8]);
+
+impl Homography {
+    /// Solves the mapping taking each of `from` to the matching point of `to`.
+    fn from_points(from: &[(f64, f64); 4], to: &[(f64, f64); 4]) -> Result<Self> {
+        let mut rows = [[0.0; 9]; 8];
+        for (i, (&(u, v), &(x, y))) in from.iter().zip(to).enumerate() {
+            rows[2 * i] = [u, v, 1.0, 0.0, 0.0, 0.0, -u * x, -v * x, x];
+            rows[2 * i + 1] = [0.0, 0.0, 0.0, u, v, 1.0, -u * y, -v * y, y];
+        }
+        for column in 0..8 {
+            let pivot = (column..8)
+                .max_by(|&a, &b| rows[a][column].abs().total_cmp(&rows[b][column].abs()))
+                .unwrap_or(column);
+            if rows[pivot][column].abs() < 1e-10 {
+                return Err(anyhow!("The corner points must form a quadrilateral"));
+            }
+            rows.swap(column, pivot);
+            for row in 0..8 {
+                if row == column {
+                    continue;
+                }
+                let factor = rows[row][column] / rows[column][column];
+                for k in column..9 {
+                    rows[row][k] -= factor * rows[column][k];
+                }
+            }
+        }
+        let mut coefficients = [0.0; 8];
+        for (i, coefficient) in coefficients.iter_mut().enumerate() {
+            *coefficient = rows[i][8] / rows[i][i];
+        }
+        Ok(Self(coefficients))
+    }
+
+    fn apply(&self, x: f64, y: f64) -> (f64, f64) {
+        let h = &self.0;
+        let w = h[6] * x + h[7] * y + 1.0;
+        ((h[0] * x + h[1] * y + h[2]) / w, (h[3] * x + h[4] * y + h[5]) / w)
+    }
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// Sorts four points clockwise starting from the top left one.
+pub fn order_corners(points: &[(f64, f64); 4]) -> [(f64, f64); 4] {
+    let center_x = points.iter().map(|p| p.0).sum::<f64>() / 4.0;
+    let center_y = points.iter().map(|p| p.1).sum::<f64>() / 4.0;
+    let mut sorted = *points;
+    sorted.sort_by(|a, b| {
+        let angle_a = (a.1 - center_y).atan2(a.0 - center_x);
+        let angle_b = (b.1 - center_y).atan2(b.0 - center_x);
+        angle_a.total_cmp(&angle_b)
+    });
+    let first = (0..4)
+        .min_by(|&a, &b| (sorted[a].0 + sorted[a].1).total_cmp(&(sorted[b].0 + sorted[b].1)))
+        .unwrap_or(0);
+    sorted.rotate_left(first);
+    sorted
+}
+
+/// Rectangle size matching the longer of each pair of opposite edges of the ordered corners.
+pub fn estimate_rectangle_size(corners: &[(f64, f64); 4]) -> (f64, f64) {
+    let [top_left, top_right, bottom_right, bottom_left] = *corners;
+    (
+        distance(top_left, top_right).max(distance(bottom_left, bottom_right)),
+        distance(top_left, bottom_left).max(distance(top_right, bottom_right)),
+    )
+}
+
+/// Maps the quadrilateral of the ordered corners onto an upright rectangle.
+#[derive(Debug, Clone)]
+pub struct Perspective {
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    homography: Homography,
+    interpolation: Interpolation,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Perspective {
+    pub fn new(data: &[u8], width: u32, height: u32, corners: &[(f64, f64); 4],
+               output_width: u32, output_height: u32, interpolation: Interpolation) -> Result<Self> {
+        if output_width == 0 || output_height == 0 {
+            return Err(anyhow!("Output size must not be empty"));
+        }
+        check_image_size(output_width, output_height)?;
+        let (w, h) = (output_width as f64, output_height as f64);
+        let rectangle = [(0.0, 0.0), (w, 0.0), (w, h), (0.0, h)];
+        Ok(Self {
+            source: data.to_vec(),
+            source_size: (width, height),
+            homography: Homography::from_points(&rectangle, corners)?,
+            interpolation,
+            size: (output_width, output_height),
+            cursor: RowCursor::with_cost(output_width, output_height, interpolation.cost()),
+        })
+    }
+}
+
+impl Filter for Perspective {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let width = self.size.0;
+        let (source_width, source_height) = self.source_size;
+        let sampler = Sampler::new(&self.source, source_width, source_height, self.interpolation);
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                let output = &mut data[row_bytes(&(y..y + 1), width)];
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    let (sx, sy) = self.homography.apply(x as f64 + 0.5, y as f64 + 0.5);
+                    let inside = sx >= 0.0 && sy >= 0.0 &&
+                        sx <= source_width as f64 && sy <= source_height as f64;
+                    pixel.copy_from_slice(&if inside { sampler.sample(sx, sy) } else { [0; 4] });
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: (f64, f64), b: (f64, f64)) {
+        assert!(distance(a, b) < 1e-6, "{:?} is not {:?}", a, b);
+    }
+
+    #[test]
+    fn order_corners_starts_top_left_and_goes_clockwise() {
+        let expected = [(10.0, 12.0), (95.0, 5.0), (100.0, 80.0), (3.0, 90.0)];
+        let orders = [
+            [expected[2], expected[0], expected[3], expected[1]],
+            [expected[3], expected[2], expected[1], expected[0]],
+            expected,
+        ];
+        for points in orders {
+            assert_eq!(order_corners(&points), expected);
+        }
+    }
+
+    #[test]
+    fn homography_maps_the_four_corners_exactly() {
+        let rectangle = [(0.0, 0.0), (200.0, 0.0), (200.0, 100.0), (0.0, 100.0)];
+        let corners = [(10.0, 12.0), (95.0, 5.0), (100.0, 80.0), (3.0, 90.0)];
+        let homography = Homography::from_points(&rectangle, &corners).unwrap();
+        for (from, to) in rectangle.iter().zip(&corners) {
+            assert_close(homography.apply(from.0, from.1), *to);
+        }
+    }
+
+    #[test]
+    fn homography_of_a_rectangle_is_a_scale() {
+        let from = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let to = [(0.0, 0.0), (20.0, 0.0), (20.0, 30.0), (0.0, 30.0)];
+        let homography = Homography::from_points(&from, &to).unwrap();
+        assert_close(homography.apply(5.0, 5.0), (10.0, 15.0));
+    }
+
+    #[test]
+    fn homography_of_collinear_points_is_an_error() {
+        let from = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+        let to = [(0.0, 0.0), (1.0, 1.0), (2.0, 2.0), (3.0, 3.0)];
+        assert!(Homography::from_points(&from, &to).is_err());
+    }
+
+    #[test]
+    fn output_over_the_limit_is_an_error() {
+        let data = vec![0; 4];
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        assert!(Perspective::new(&data, 1, 1, &corners, 100, 100, Interpolation::Bilinear).is_ok());
+        assert!(Perspective::new(&data, 1, 1, &corners, 10000, 10000, Interpolation::Bilinear).is_err());
+    }
+}
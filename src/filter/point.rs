@@ -0,0 +1,102 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, row_bytes};
+use super::color::{clamp_u8, luminance};
+
+/// Operations that map every pixel independently of its neighbours.
+#[derive(Debug, Clone, Copy)]
+pub enum PointOperation {
+    Invert { preserve_alpha: bool },
+    Sepia { strength: f64 },
+    Posterize { levels: u32 },
+    Solarize { threshold: u8 },
+    Duotone { dark: [u8; 3], light: [u8; 3] },
+}
+
+impl PointOperation {
+    fn validate(&self) -> Result<()> {
+        match *self {
+            PointOperation::Sepia { strength } if !(0.0..=1.0).contains(&strength) => {
+                Err(anyhow!("Sepia strength must be in 0..=1: {}", strength))
+            },
+            PointOperation::Posterize { levels } if !(2..=256).contains(&levels) => {
+                Err(anyhow!("Posterize levels must be in 2..=256: {}", levels))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&self, pixel: &mut [u8]) {
+        match *self {
+            PointOperation::Invert { preserve_alpha } => {
+                let end = if preserve_alpha { 3 } else { 4 };
+                for value in pixel.iter_mut().take(end) {
+                    *value = 255 - *value;
+                }
+            },
+            PointOperation::Sepia { strength } => {
+                let (r, g, b) = (pixel[0] as f64, pixel[1] as f64, pixel[2] as f64);
+                let sepia = [
+                    0.393 * r + 0.769 * g + 0.189 * b,
+                    0.349 * r + 0.686 * g + 0.168 * b,
+                    0.272 * r + 0.534 * g + 0.131 * b,
+                ];
+                for (value, target) in pixel.iter_mut().zip(sepia) {
+                    *value = clamp_u8(*value as f64 * (1.0 - strength) + target * strength);
+                }
+            },
+            PointOperation::Posterize { levels } => {
+                let steps = (levels - 1) as f64;
+                for value in pixel.iter_mut().take(3) {
+                    let level = (*value as f64 * steps / 255.0).round();
+                    *value = clamp_u8(level * 255.0 / steps);
+                }
+            },
+            PointOperation::Solarize { threshold } => {
+                for value in pixel.iter_mut().take(3) {
+                    if *value >= threshold {
+                        *value = 255 - *value;
+                    }
+                }
+            },
+            PointOperation::Duotone { dark, light } => {
+                let t = luminance(pixel[0], pixel[1], pixel[2]) / 255.0;
+                for (channel, value) in pixel.iter_mut().take(3).enumerate() {
+                    *value = clamp_u8(dark[channel] as f64 * (1.0 - t) + light[channel] as f64 * t);
+                }
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PointFilter {
+    operation: PointOperation,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl PointFilter {
+    pub fn new(operation: PointOperation, width: u32, height: u32) -> Result<Self> {
+        operation.validate()?;
+        Ok(Self {
+            operation,
+            size: (width, height),
+            cursor: RowCursor::new(width, height),
+        })
+    }
+}
+
+impl Filter for PointFilter {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.cursor.next_rows() {
+            for pixel in data[row_bytes(&rows, self.size.0)].chunks_exact_mut(4) {
+                self.operation.apply(pixel);
+            }
+        }
+        self.cursor.is_done()
+    }
+}
@@ -0,0 +1,10 @@
+/// Copies the `width` x `height` block at `(x, y)` out of an RGBA buffer.
+pub fn copy_region(data: &[u8], image_width: u32,
+                   x: u32, y: u32, width: u32, height: u32) -> Vec<u8> {
+    let mut region = Vec::with_capacity(width as usize * height as usize * 4);
+    for row in y..y + height {
+        let start = ((row * image_width + x) * 4) as usize;
+        region.extend_from_slice(&data[start..start + width as usize * 4]);
+    }
+    region
+}
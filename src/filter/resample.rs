@@ -0,0 +1,113 @@
+use std::str::FromStr;
+use anyhow::{Error, anyhow};
+use super::color::clamp_u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    Nearest,
+    Bilinear,
+    Bicubic,
+}
+
+impl Interpolation {
+    /// Relative cost per output pixel, used to size the chunks.
+    pub fn cost(self) -> u32 {
+        match self {
+            Interpolation::Nearest => 1,
+            Interpolation::Bilinear => 4,
+            Interpolation::Bicubic => 16,
+        }
+    }
+}
+
+impl FromStr for Interpolation {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "nearest" => Ok(Interpolation::Nearest),
+            "bilinear" => Ok(Interpolation::Bilinear),
+            "bicubic" => Ok(Interpolation::Bicubic),
+            _ => Err(anyhow!("Unknown interpolation {:#?}", value)),
+        }
+    }
+}
+
+/// Catmull-Rom weight of a sample `distance` pixels away.
+fn cubic_weight(distance: f64) -> f64 {
+    let distance = distance.abs();
+    if distance < 1.0 {
+        (1.5 * distance - 2.5) * distance * distance + 1.0
+    } else if distance < 2.0 {
+        ((-0.5 * distance + 2.5) * distance - 4.0) * distance + 2.0
+    } else {
+        0.0
+    }
+}
+
+/// Reads RGBA images at fractional positions, interpolating with premultiplied alpha.
+#[derive(Debug, Clone)]
+pub struct Sampler<'a> {
+    data: &'a [u8],
+    size: (u32, u32),
+    interpolation: Interpolation,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(data: &'a [u8], width: u32, height: u32, interpolation: Interpolation) -> Self {
+        Self { data, size: (width, height), interpolation }
+    }
+
+    fn premultiplied(&self, x: i64, y: i64) -> [f64; 4] {
+        let (width, height) = self.size;
+        let x = x.clamp(0, width as i64 - 1);
+        let y = y.clamp(0, height as i64 - 1);
+        let index = (y * width as i64 + x) as usize * 4;
+        let pixel = &self.data[index..index + 4];
+        let alpha = pixel[3] as f64 / 255.0;
+        [pixel[0] as f64 * alpha, pixel[1] as f64 * alpha, pixel[2] as f64 * alpha, pixel[3] as f64]
+    }
+
+    fn weighted_sum(&self, x0: i64, y0: i64, weights_x: &[f64], weights_y: &[f64]) -> [f64; 4] {
+        let mut sum = [0.0; 4];
+        for (dy, weight_y) in weights_y.iter().enumerate() {
+            for (dx, weight_x) in weights_x.iter().enumerate() {
+                let pixel = self.premultiplied(x0 + dx as i64, y0 + dy as i64);
+                for (sum, value) in sum.iter_mut().zip(pixel) {
+                    *sum += value * weight_x * weight_y;
+                }
+            }
+        }
+        sum
+    }
+
+    /// Color at `(x, y)` in image coordinates, where pixel `i` covers `i..i + 1`.
+    pub fn sample(&self, x: f64, y: f64) -> [u8; 4] {
+        let (x, y) = (x - 0.5, y - 0.5);
+        let sum = match self.interpolation {
+            Interpolation::Nearest => self.premultiplied(x.round() as i64, y.round() as i64),
+            Interpolation::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                self.weighted_sum(x0 as i64, y0 as i64, &[1.0 - fx, fx], &[1.0 - fy, fy])
+            },
+            Interpolation::Bicubic => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let weights_x: Vec<f64> = (-1..=2).map(|k| cubic_weight(k as f64 - fx)).collect();
+                let weights_y: Vec<f64> = (-1..=2).map(|k| cubic_weight(k as f64 - fy)).collect();
+                self.weighted_sum(x0 as i64 - 1, y0 as i64 - 1, &weights_x, &weights_y)
+            },
+        };
+        let alpha = sum[3].clamp(0.0, 255.0);
+        if alpha <= 0.0 {
+            return [0; 4];
+        }
+        [
+            clamp_u8(sum[0] * 255.0 / alpha),
+            clamp_u8(sum[1] * 255.0 / alpha),
+            clamp_u8(sum[2] * 255.0 / alpha),
+            clamp_u8(alpha),
+        ]
+    }
+}
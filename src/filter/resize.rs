@@ -0,0 +1,262 @@
+use std::f64::consts::PI;
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, row_bytes, check_image_size};
+use super::color::clamp_u8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeFilter {
+    Nearest,
+    /// Averages the covered source area, the usual choice for shrinking.
+    Area,
+    Bilinear,
+    Bicubic,
+    Lanczos3,
+}
+
+impl ResizeFilter {
+    fn support(self) -> f64 {
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Area => 0.5,
+            ResizeFilter::Bilinear => 1.0,
+            ResizeFilter::Bicubic => 2.0,
+            ResizeFilter::Lanczos3 => 3.0,
+        }
+    }
+
+    fn weight(self, x: f64) -> f64 {
+        let x = x.abs();
+        match self {
+            ResizeFilter::Nearest | ResizeFilter::Area => if x < 0.5 { 1.0 } else { 0.0 },
+            ResizeFilter::Bilinear => (1.0 - x).max(0.0),
+            ResizeFilter::Bicubic => {
+                if x < 1.0 {
+                    (1.5 * x - 2.5) * x * x + 1.0
+                } else if x < 2.0 {
+                    ((-0.5 * x + 2.5) * x - 4.0) * x + 2.0
+                } else {
+                    0.0
+                }
+            },
+            ResizeFilter::Lanczos3 => {
+                if x < 1e-8 {
+                    1.0
+                } else if x < 3.0 {
+                    let pi_x = PI * x;
+                    3.0 * pi_x.sin() * (pi_x / 3.0).sin() / (pi_x * pi_x)
+                } else {
+                    0.0
+                }
+            },
+        }
+    }
+}
+
+impl FromStr for ResizeFilter {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "nearest" => Ok(ResizeFilter::Nearest),
+            "area" => Ok(ResizeFilter::Area),
+            "bilinear" => Ok(ResizeFilter::Bilinear),
+            "bicubic" => Ok(ResizeFilter::Bicubic),
+            "lanczos3" => Ok(ResizeFilter::Lanczos3),
+            _ => Err(anyhow!("Unknown resize filter {:#?}", value)),
+        }
+    }
+}
+
+/// Source pixels and normalized weights making up one output pixel along an axis.
+#[derive(Debug, Clone)]
+struct Contribution {
+    start: usize,
+    weights: Vec<f32>,
+}
+
+/// Weights along one axis. When shrinking, the kernel is stretched over the
+/// source pixels covered by an output pixel so every source pixel is averaged in.
+fn contributions(source_length: u32, target_length: u32, filter: ResizeFilter) -> Vec<Contribution> {
+    let scale = source_length as f64 / target_length as f64;
+    (0..target_length)
+        .map(|target| {
+            let center = (target as f64 + 0.5) * scale;
+            match filter {
+                ResizeFilter::Nearest => Contribution {
+                    start: (center as usize).min(source_length as usize - 1),
+                    weights: vec![1.0],
+                },
+                ResizeFilter::Area => {
+                    let (left, right) = (center - scale / 2.0, center + scale / 2.0);
+                    let start = left.floor().max(0.0) as usize;
+                    let end = (right.ceil() as usize).min(source_length as usize).max(start + 1);
+                    let weights: Vec<f64> = (start..end)
+                        .map(|source| (right.min(source as f64 + 1.0) - left.max(source as f64)).max(0.0))
+                        .collect();
+                    normalized(start, weights)
+                },
+                _ => {
+                    let filter_scale = scale.max(1.0);
+                    let support = filter.support() * filter_scale;
+                    let start = (center - support).floor().max(0.0) as usize;
+                    let end = ((center + support).ceil() as usize).min(source_length as usize).max(start + 1);
+                    let weights: Vec<f64> = (start..end)
+                        .map(|source| filter.weight((source as f64 + 0.5 - center) / filter_scale))
+                        .collect();
+                    normalized(start, weights)
+                },
+            }
+        })
+        .collect()
+}
+
+fn normalized(start: usize, weights: Vec<f64>) -> Contribution {
+    let total: f64 = weights.iter().sum();
+    let weights = if total.abs() > 1e-12 {
+        weights.iter().map(|weight| (weight / total) as f32).collect()
+    } else {
+        vec![1.0]
+    };
+    Contribution { start, weights }
+}
+
+/// Separable resize of premultiplied RGBA, horizontally into an intermediate buffer then vertically.
+#[derive(Debug, Clone)]
+pub struct Resize {
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    horizontal_weights: Vec<Contribution>,
+    vertical_weights: Vec<Contribution>,
+    temp: Vec<f32>,
+    size: (u32, u32),
+    horizontal: RowCursor,
+    vertical: RowCursor,
+}
+
+impl Resize {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               target_width: u32, target_height: u32, filter: ResizeFilter) -> Result<Self> {
+        if target_width == 0 || target_height == 0 {
+            return Err(anyhow!("Target size must not be empty"));
+        }
+        check_image_size(target_width, target_height)?;
+        // The horizontal pass keeps the source height.
+        check_image_size(target_width, height)?;
+        let horizontal_weights = contributions(width, target_width, filter);
+        let vertical_weights = contributions(height, target_height, filter);
+        let horizontal_cost = horizontal_weights.iter().map(|c| c.weights.len()).max().unwrap_or(1);
+        let vertical_cost = vertical_weights.iter().map(|c| c.weights.len()).max().unwrap_or(1);
+        Ok(Self {
+            source: data.to_vec(),
+            source_size: (width, height),
+            horizontal_weights,
+            vertical_weights,
+            temp: vec![0.0; target_width as usize * height as usize * 4],
+            size: (target_width, target_height),
+            horizontal: RowCursor::with_cost(target_width, height, horizontal_cost as u32),
+            vertical: RowCursor::with_cost(target_width, target_height, vertical_cost as u32),
+        })
+    }
+
+    fn horizontal_row(&mut self, y: u32) {
+        let source = &self.source[row_bytes(&(y..y + 1), self.source_size.0)];
+        let output = &mut self.temp[row_bytes(&(y..y + 1), self.size.0)];
+        for (pixel, contribution) in output.chunks_exact_mut(4).zip(&self.horizontal_weights) {
+            let mut sum = [0.0; 4];
+            for (offset, weight) in contribution.weights.iter().enumerate() {
+                let index = (contribution.start + offset) * 4;
+                let value = &source[index..index + 4];
+                let alpha = value[3] as f32 / 255.0;
+                sum[0] += value[0] as f32 * alpha * weight;
+                sum[1] += value[1] as f32 * alpha * weight;
+                sum[2] += value[2] as f32 * alpha * weight;
+                sum[3] += value[3] as f32 * weight;
+            }
+            pixel.copy_from_slice(&sum);
+        }
+    }
+
+    fn vertical_row(&self, y: u32, data: &mut [u8]) {
+        let width = self.size.0;
+        let contribution = &self.vertical_weights[y as usize];
+        let mut sum = vec![0.0; width as usize * 4];
+        for (offset, weight) in contribution.weights.iter().enumerate() {
+            let row = (contribution.start + offset) as u32;
+            for (sum, value) in sum.iter_mut().zip(&self.temp[row_bytes(&(row..row + 1), width)]) {
+                *sum += value * weight;
+            }
+        }
+        let output = &mut data[row_bytes(&(y..y + 1), width)];
+        for (pixel, sum) in output.chunks_exact_mut(4).zip(sum.chunks_exact(4)) {
+            let alpha = sum[3].clamp(0.0, 255.0);
+            for (value, sum) in pixel.iter_mut().zip(sum).take(3) {
+                *value = if alpha > 0.0 {
+                    clamp_u8((sum * 255.0 / alpha) as f64)
+                } else {
+                    0
+                };
+            }
+            pixel[3] = clamp_u8(alpha as f64);
+        }
+    }
+}
+
+impl Filter for Resize {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.horizontal.next_rows() {
+            for y in rows {
+                self.horizontal_row(y);
+            }
+            return false;
+        }
+        if let Some(rows) = self.vertical.next_rows() {
+            for y in rows {
+                self.vertical_row(y, data);
+            }
+        }
+        self.vertical.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::MAX_IMAGE_SIDE;
+
+    const FILTERS: [ResizeFilter; 5] = [
+        ResizeFilter::Nearest,
+        ResizeFilter::Area,
+        ResizeFilter::Bilinear,
+        ResizeFilter::Bicubic,
+        ResizeFilter::Lanczos3,
+    ];
+
+    #[test]
+    fn contribution_weights_sum_to_one() {
+        for filter in FILTERS {
+            for (source_length, target_length) in [(100, 37), (37, 100), (10, 10), (1, 5), (5, 1)] {
+                let contributions = contributions(source_length, target_length, filter);
+                assert_eq!(contributions.len(), target_length as usize);
+                for contribution in contributions {
+                    let sum: f32 = contribution.weights.iter().sum();
+                    assert!((sum - 1.0).abs() < 1e-5, "{:?} {} -> {}: {}",
+                        filter, source_length, target_length, sum);
+                    assert!(contribution.start + contribution.weights.len() <= source_length as usize);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn new_rejects_targets_over_the_limit() {
+        let data = [0; 4 * 4];
+        assert!(Resize::new(&data, 2, 2, 0, 10, ResizeFilter::Bilinear).is_err());
+        assert!(Resize::new(&data, 2, 2, MAX_IMAGE_SIDE + 1, 1, ResizeFilter::Bilinear).is_err());
+        assert!(Resize::new(&data, 2, 2, 10000, 10000, ResizeFilter::Bilinear).is_err());
+        assert!(Resize::new(&data, 2, 2, 3, 3, ResizeFilter::Bilinear).is_ok());
+    }
+}
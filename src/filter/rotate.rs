@@ -0,0 +1,161 @@
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, RowCursor, row_bytes, check_image_size};
+use super::resample::{Interpolation, Sampler};
+
+/// How the output is sized around the rotated image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotateFit {
+    /// Grows the canvas so the whole image stays visible.
+    Expand,
+    /// Keeps the largest upright rectangle inside the rotated image.
+    Crop,
+}
+
+impl FromStr for RotateFit {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "expand" => Ok(RotateFit::Expand),
+            "crop" => Ok(RotateFit::Crop),
+            _ => Err(anyhow!("Unknown rotate fit {:#?}", value)),
+        }
+    }
+}
+
+/// Size of the largest axis aligned rectangle inside a `width` x `height` rectangle rotated by `angle` radians.
+fn largest_inner_size(width: f64, height: f64, angle: f64) -> (f64, f64) {
+    let (sin, cos) = (angle.sin().abs(), angle.cos().abs());
+    let (long, short) = if width >= height { (width, height) } else { (height, width) };
+    if short <= 2.0 * sin * cos * long || (sin - cos).abs() < 1e-10 {
+        let half = 0.5 * short;
+        if width >= height {
+            (half / sin, half / cos)
+        } else {
+            (half / cos, half / sin)
+        }
+    } else {
+        let cos_2a = cos * cos - sin * sin;
+        ((width * cos - height * sin) / cos_2a, (height * cos - width * sin) / cos_2a)
+    }
+}
+
+/// Rotation by an arbitrary angle around the image center, clockwise for positive angles.
+#[derive(Debug, Clone)]
+pub struct Rotate {
+    source: Vec<u8>,
+    source_size: (u32, u32),
+    interpolation: Interpolation,
+    sin: f64,
+    cos: f64,
+    fill: [u8; 4],
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl Rotate {
+    pub fn new(data: &[u8], width: u32, height: u32, degrees: f64,
+               interpolation: Interpolation, fit: RotateFit, fill: [u8; 4]) -> Result<Self> {
+        if !degrees.is_finite() {
+            return Err(anyhow!("Angle must be a finite number"));
+        }
+        let angle = degrees.to_radians();
+        let (sin, cos) = angle.sin_cos();
+        let (w, h) = (width as f64, height as f64);
+        let (output_width, output_height) = match fit {
+            RotateFit::Expand => (w * cos.abs() + h * sin.abs(), w * sin.abs() + h * cos.abs()),
+            RotateFit::Crop => largest_inner_size(w, h, angle),
+        };
+        // Expanding rounds up to keep every corner, cropping rounds down to stay inside the image.
+        // The epsilon absorbs rounding noise at multiples of 90 degrees.
+        let round = |value: f64| match fit {
+            RotateFit::Expand => ((value - 1e-6).ceil() as u32).max(1),
+            RotateFit::Crop => ((value + 1e-6).floor() as u32).max(1),
+        };
+        let size = (round(output_width), round(output_height));
+        check_image_size(size.0, size.1)?;
+        Ok(Self {
+            source: data.to_vec(),
+            source_size: (width, height),
+            interpolation,
+            sin,
+            cos,
+            fill,
+            size,
+            cursor: RowCursor::with_cost(size.0, size.1, interpolation.cost()),
+        })
+    }
+}
+
+impl Filter for Rotate {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        let (width, height) = self.size;
+        let (source_width, source_height) = (self.source_size.0 as f64, self.source_size.1 as f64);
+        let sampler = Sampler::new(&self.source, self.source_size.0, self.source_size.1, self.interpolation);
+        if let Some(rows) = self.cursor.next_rows() {
+            for y in rows {
+                let output = &mut data[row_bytes(&(y..y + 1), width)];
+                let dy = y as f64 + 0.5 - height as f64 / 2.0;
+                for (x, pixel) in output.chunks_exact_mut(4).enumerate() {
+                    let dx = x as f64 + 0.5 - width as f64 / 2.0;
+                    let sx = self.cos * dx + self.sin * dy + source_width / 2.0;
+                    let sy = -self.sin * dx + self.cos * dy + source_height / 2.0;
+                    let inside = sx >= 0.0 && sy >= 0.0 && sx <= source_width && sy <= source_height;
+                    pixel.copy_from_slice(&if inside { sampler.sample(sx, sy) } else { self.fill });
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close((width, height): (f64, f64), expected: (f64, f64)) {
+        assert!((width - expected.0).abs() < 1e-6 && (height - expected.1).abs() < 1e-6,
+            "{:?} is not {:?}", (width, height), expected);
+    }
+
+    #[test]
+    fn largest_inner_size_of_quarter_turns() {
+        assert_close(largest_inner_size(300.0, 200.0, 0.0), (300.0, 200.0));
+        assert_close(largest_inner_size(300.0, 200.0, std::f64::consts::FRAC_PI_2), (200.0, 300.0));
+        assert_close(largest_inner_size(300.0, 200.0, std::f64::consts::PI), (300.0, 200.0));
+    }
+
+    #[test]
+    fn largest_inner_size_of_square_at_45_degrees() {
+        let side = 100.0 / 2f64.sqrt();
+        assert_close(largest_inner_size(100.0, 100.0, std::f64::consts::FRAC_PI_4), (side, side));
+    }
+
+    #[test]
+    fn expanded_size_over_the_limit_is_an_error() {
+        let data = vec![0; 4];
+        // A square turned by 45 degrees needs about 1.41 times its side.
+        assert!(Rotate::new(&data, 1000, 1000, 45.0, Interpolation::Bilinear, RotateFit::Expand, [0; 4]).is_ok());
+        assert!(Rotate::new(&data, 12000, 12000, 45.0, Interpolation::Bilinear, RotateFit::Expand, [0; 4]).is_err());
+    }
+
+    #[test]
+    fn largest_inner_size_fits_inside_the_rotated_image() {
+        for (width, height) in [(300.0, 200.0), (200.0, 300.0), (1000.0, 50.0), (64.0, 64.0)] {
+            for degrees in [1.0, 5.0, 17.5, 30.0, 44.0, 60.0, 89.0, -12.0] {
+                let angle = f64::to_radians(degrees);
+                let (inner_width, inner_height) = largest_inner_size(width, height, angle);
+                let (sin, cos) = (angle.sin().abs(), angle.cos().abs());
+                // The inner rectangle turned back into the image frame stays inside the image.
+                assert!(inner_width * cos + inner_height * sin <= width + 1e-6);
+                assert!(inner_width * sin + inner_height * cos <= height + 1e-6);
+                assert!(inner_width > 0.0 && inner_height > 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, EdgeMode, Blur, BlurKind, row_bytes};
+use super::color::clamp_u8;
+
+/// Adds back `amount` times the difference to a Gaussian blur
+/// wherever it is at least `threshold`.
+#[derive(Debug, Clone)]
+pub struct UnsharpMask {
+    blur: Blur,
+    blurred: Vec<u8>,
+    blur_done: bool,
+    source: Vec<u8>,
+    amount: f64,
+    threshold: u8,
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl UnsharpMask {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               amount: f64, radius: u32, threshold: u8) -> Result<Self> {
+        if amount < 0.0 {
+            return Err(anyhow!("Amount must not be negative: {}", amount));
+        }
+        Ok(Self {
+            blur: Blur::new(BlurKind::Gaussian, data, width, height, radius, EdgeMode::Clamp)?,
+            blurred: data.to_vec(),
+            blur_done: false,
+            source: data.to_vec(),
+            amount,
+            threshold,
+            size: (width, height),
+            cursor: RowCursor::new(width, height),
+        })
+    }
+}
+
+impl Filter for UnsharpMask {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if !self.blur_done {
+            self.blur_done = self.blur.step(&mut self.blurred);
+            return false;
+        }
+        if let Some(rows) = self.cursor.next_rows() {
+            let range = row_bytes(&rows, self.size.0);
+            let source = self.source[range.clone()].chunks_exact(4);
+            let blurred = self.blurred[range.clone()].chunks_exact(4);
+            for ((pixel, source), blurred) in data[range].chunks_exact_mut(4).zip(source).zip(blurred) {
+                for channel in 0..3 {
+                    let difference = source[channel] as f64 - blurred[channel] as f64;
+                    if difference.abs() >= self.threshold as f64 {
+                        pixel[channel] = clamp_u8(source[channel] as f64 + self.amount * difference);
+                    }
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
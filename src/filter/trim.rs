@@ -0,0 +1,198 @@
+use std::ops::Range;
+use std::str::FromStr;
+use anyhow::{Error, Result, anyhow};
+use super::{Filter, Crop};
+
+/// Which pixels count as border when trimming.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimMode {
+    /// Pixels close to the color in the corner of the side.
+    Uniform,
+    /// Pixels with alpha at most the tolerance.
+    Transparent,
+}
+
+impl FromStr for TrimMode {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "uniform" => Ok(TrimMode::Uniform),
+            "transparent" => Ok(TrimMode::Transparent),
+            _ => Err(anyhow!("Unknown trim mode {:#?}", value)),
+        }
+    }
+}
+
+/// Number of pixels removed from each side.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrimmedSides {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+struct BorderTest<'a> {
+    data: &'a [u8],
+    width: u32,
+    mode: TrimMode,
+    tolerance: u8,
+}
+
+impl BorderTest<'_> {
+    fn pixel(&self, x: u32, y: u32) -> &[u8] {
+        let index = (y * self.width + x) as usize * 4;
+        &self.data[index..index + 4]
+    }
+
+    fn is_border(&self, x: u32, y: u32, reference: &[u8]) -> bool {
+        let pixel = self.pixel(x, y);
+        match self.mode {
+            TrimMode::Uniform => pixel
+                .iter()
+                .zip(reference)
+                .all(|(value, reference)| value.abs_diff(*reference) <= self.tolerance),
+            TrimMode::Transparent => pixel[3] <= self.tolerance,
+        }
+    }
+
+    fn row_is_border(&self, y: u32, mut columns: Range<u32>, reference: &[u8]) -> bool {
+        columns.all(|x| self.is_border(x, y, reference))
+    }
+
+    fn column_is_border(&self, x: u32, mut rows: Range<u32>, reference: &[u8]) -> bool {
+        rows.all(|y| self.is_border(x, y, reference))
+    }
+}
+
+/// Crops away uniform or transparent borders, keeping `margin` pixels of them.
+#[derive(Debug, Clone)]
+pub struct Trim {
+    crop: Crop,
+    trimmed: TrimmedSides,
+}
+
+impl Trim {
+    pub fn new(data: &[u8], width: u32, height: u32,
+               mode: TrimMode, tolerance: u8, margin: u32) -> Result<Self> {
+        let test = BorderTest { data, width, mode, tolerance };
+        // Top and left compare with the top left corner, bottom and right with the bottom right one.
+        let top_left = test.pixel(0, 0).to_vec();
+        let bottom_right = test.pixel(width - 1, height - 1).to_vec();
+        let mut top = 0;
+        while top < height && test.row_is_border(top, 0..width, &top_left) {
+            top += 1;
+        }
+        if top == height {
+            return Err(anyhow!("The whole image is border"));
+        }
+        let mut bottom = height;
+        while bottom > top + 1 && test.row_is_border(bottom - 1, 0..width, &bottom_right) {
+            bottom -= 1;
+        }
+        let mut left = 0;
+        while left < width - 1 && test.column_is_border(left, top..bottom, &top_left) {
+            left += 1;
+        }
+        let mut right = width;
+        while right > left + 1 && test.column_is_border(right - 1, top..bottom, &bottom_right) {
+            right -= 1;
+        }
+        let top = top.saturating_sub(margin);
+        let left = left.saturating_sub(margin);
+        let bottom = bottom.saturating_add(margin).min(height);
+        let right = right.saturating_add(margin).min(width);
+        let trimmed = TrimmedSides {
+            top,
+            bottom: height - bottom,
+            left,
+            right: width - right,
+        };
+        Ok(Self {
+            crop: Crop::new(data, width, height, left, top, right - left, bottom - top)?,
+            trimmed,
+        })
+    }
+
+    pub fn trimmed(&self) -> TrimmedSides {
+        self.trimmed
+    }
+}
+
+impl Filter for Trim {
+    fn output_size(&self) -> (u32, u32) {
+        self.crop.output_size()
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        self.crop.step(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 8x6 image with a 3x3 dark block at (2, 1), on a light border whose
+    /// pixels differ by up to 3 from the corner color.
+    fn framed() -> Vec<u8> {
+        let mut data = Vec::new();
+        for y in 0..6 {
+            for x in 0..8 {
+                if (2..5).contains(&x) && (1..4).contains(&y) {
+                    data.extend([20, 30, 40, 255]);
+                } else {
+                    let noise = ((x + y) % 4) as u8;
+                    data.extend([250 - noise, 250, 250, 255]);
+                }
+            }
+        }
+        data
+    }
+
+    fn sides(top: u32, bottom: u32, left: u32, right: u32) -> TrimmedSides {
+        TrimmedSides { top, bottom, left, right }
+    }
+
+    #[test]
+    fn uniform_border_within_tolerance_is_trimmed() {
+        let trim = Trim::new(&framed(), 8, 6, TrimMode::Uniform, 3, 0).unwrap();
+        assert_eq!(trim.trimmed(), sides(1, 2, 2, 3));
+        assert_eq!(trim.output_size(), (3, 3));
+    }
+
+    #[test]
+    fn noise_over_tolerance_is_kept() {
+        let trim = Trim::new(&framed(), 8, 6, TrimMode::Uniform, 0, 0).unwrap();
+        assert_eq!(trim.trimmed(), sides(0, 0, 0, 0));
+    }
+
+    #[test]
+    fn margin_keeps_part_of_the_border() {
+        let trim = Trim::new(&framed(), 8, 6, TrimMode::Uniform, 3, 1).unwrap();
+        assert_eq!(trim.trimmed(), sides(0, 1, 1, 2));
+        assert_eq!(trim.output_size(), (5, 5));
+    }
+
+    #[test]
+    fn huge_margin_keeps_the_whole_image() {
+        let trim = Trim::new(&framed(), 8, 6, TrimMode::Uniform, 3, u32::MAX).unwrap();
+        assert_eq!(trim.trimmed(), sides(0, 0, 0, 0));
+        assert_eq!(trim.output_size(), (8, 6));
+    }
+
+    #[test]
+    fn transparent_border_is_trimmed_by_alpha() {
+        let mut data = vec![0; 4 * 4 * 4];
+        data[(2 * 4 + 1) * 4..(2 * 4 + 1) * 4 + 4].copy_from_slice(&[255, 0, 0, 255]);
+        let trim = Trim::new(&data, 4, 4, TrimMode::Transparent, 0, 0).unwrap();
+        assert_eq!(trim.trimmed(), sides(2, 1, 1, 2));
+    }
+
+    #[test]
+    fn image_of_only_border_is_an_error() {
+        let data = vec![255; 3 * 3 * 4];
+        assert!(Trim::new(&data, 3, 3, TrimMode::Uniform, 0, 0).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Result, anyhow};
+use super::{Filter, RowCursor, row_bytes};
+use super::color::clamp_u8;
+
+fn visible_pixels(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.chunks_exact(4).filter(|pixel| pixel[3] > 0)
+}
+
+fn percentile(histogram: &[u32; 256], total: u32, percent: f64) -> f64 {
+    let target = (total as f64 * percent / 100.0).ceil().max(1.0) as u32;
+    let mut count = 0;
+    for (value, entry) in histogram.iter().enumerate() {
+        count += entry;
+        if count >= target {
+            return value as f64;
+        }
+    }
+    255.0
+}
+
+fn gains_to(target: f64, channels: [f64; 3]) -> [f64; 3] {
+    channels.map(|channel| if channel > 0.0 { target / channel } else { 1.0 })
+}
+
+/// Per-channel linear map `value * scale + offset` that removes a color cast.
+#[derive(Debug, Clone)]
+pub struct WhiteBalance {
+    scale: [f64; 3],
+    offset: [f64; 3],
+    size: (u32, u32),
+    cursor: RowCursor,
+}
+
+impl WhiteBalance {
+    fn new(width: u32, height: u32, scale: [f64; 3], offset: [f64; 3]) -> Self {
+        Self {
+            scale,
+            offset,
+            size: (width, height),
+            cursor: RowCursor::new(width, height),
+        }
+    }
+
+    /// Scales each channel so that the average color becomes gray.
+    pub fn gray_world(data: &[u8], width: u32, height: u32) -> Self {
+        let mut sum = [0.0; 3];
+        let mut count = 0;
+        for pixel in visible_pixels(data) {
+            for channel in 0..3 {
+                sum[channel] += pixel[channel] as f64;
+            }
+            count += 1;
+        }
+        let mean = sum.map(|value| value / count.max(1) as f64);
+        let gray = (mean[0] + mean[1] + mean[2]) / 3.0;
+        Self::new(width, height, gains_to(gray, mean), [0.0; 3])
+    }
+
+    /// Scales each channel so that its brightest value becomes white.
+    pub fn white_patch(data: &[u8], width: u32, height: u32) -> Self {
+        let mut max = [0.0_f64; 3];
+        for pixel in visible_pixels(data) {
+            for channel in 0..3 {
+                max[channel] = max[channel].max(pixel[channel] as f64);
+            }
+        }
+        Self::new(width, height, gains_to(255.0, max), [0.0; 3])
+    }
+
+    /// Stretches each channel so that `percent` of the pixels are clipped at both ends.
+    pub fn percentile_stretch(data: &[u8], width: u32, height: u32, percent: f64) -> Result<Self> {
+        if !(0.0..50.0).contains(&percent) {
+            return Err(anyhow!("Percentile must be in 0..50: {}", percent));
+        }
+        let mut histograms = [[0; 256]; 3];
+        let mut total = 0;
+        for pixel in visible_pixels(data) {
+            for channel in 0..3 {
+                histograms[channel][pixel[channel] as usize] += 1;
+            }
+            total += 1;
+        }
+        let mut scale = [1.0; 3];
+        let mut offset = [0.0; 3];
+        for channel in 0..3 {
+            let low = percentile(&histograms[channel], total, percent);
+            let high = percentile(&histograms[channel], total, 100.0 - percent);
+            if high > low {
+                scale[channel] = 255.0 / (high - low);
+                offset[channel] = -low * scale[channel];
+            }
+        }
+        Ok(Self::new(width, height, scale, offset))
+    }
+
+    /// Scales each channel so that the pixel at `(x, y)` becomes neutral gray.
+    pub fn neutral_point(data: &[u8], width: u32, height: u32, x: u32, y: u32) -> Result<Self> {
+        if x >= width || y >= height {
+            return Err(anyhow!("Point ({}, {}) is outside of the image", x, y));
+        }
+        let mut sum = [0.0; 3];
+        let mut count = 0;
+        for sample_y in y.saturating_sub(1)..(y + 2).min(height) {
+            for sample_x in x.saturating_sub(1)..(x + 2).min(width) {
+                let index = ((sample_y * width + sample_x) * 4) as usize;
+                for channel in 0..3 {
+                    sum[channel] += data[index + channel] as f64;
+                }
+                count += 1;
+            }
+        }
+        Ok(Self::neutral(width, height, sum.map(|value| value / count as f64)))
+    }
+
+    /// Balances the image so that `color`, picked elsewhere, becomes gray.
+    pub fn neutral_color(width: u32, height: u32, color: [u8; 3]) -> Self {
+        Self::neutral(width, height, color.map(|value| value as f64))
+    }
+
+    fn neutral(width: u32, height: u32, color: [f64; 3]) -> Self {
+        let gray = (color[0] + color[1] + color[2]) / 3.0;
+        Self::new(width, height, gains_to(gray, color), [0.0; 3])
+    }
+}
+
+impl Filter for WhiteBalance {
+    fn output_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn step(&mut self, data: &mut [u8]) -> bool {
+        if let Some(rows) = self.cursor.next_rows() {
+            for pixel in data[row_bytes(&rows, self.size.0)].chunks_exact_mut(4) {
+                for (channel, value) in pixel.iter_mut().take(3).enumerate() {
+                    *value = clamp_u8(*value as f64 * self.scale[channel] + self.offset[channel]);
+                }
+            }
+        }
+        self.cursor.is_done()
+    }
+}
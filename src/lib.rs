@@ -5,6 +5,9 @@ use wasm_bindgen::prelude::*;
 mod browser;
 mod engine;
 mod editor;
+mod filter;
+#[cfg(feature = "native-decode")]
+mod decode;
 
 fn image_editor() -> Result<()> {
     editor::setup()?;